@@ -10,6 +10,7 @@ fn bench_compile(cnt: &'static str) {
         String::new(),
         false,
         true,
+        true,
     );
 }
 
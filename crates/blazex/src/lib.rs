@@ -12,10 +12,12 @@ use bzxc_llvm_wrapper::{
     AddressSpace, OptimizationLevel,
 };
 use bzxc_parser::parser::Parser;
+use bzxc_shared::Type;
 use bzxc_type_system::TypeChecker;
 use std::path::Path;
 use std::time::SystemTime;
 
+#[allow(clippy::too_many_arguments)]
 pub fn compile(
     file_name: String,
     cnt: String,
@@ -23,6 +25,7 @@ pub fn compile(
     watch: bool,
     out_file: String,
     llvm: bool,
+    bounds_check: bool,
     jit_: bool,
 ) -> i32 {
     let time = SystemTime::now();
@@ -56,7 +59,18 @@ pub fn compile(
         }
     }
 
-    let typed = TypeChecker::new(parsed.node.unwrap()).typed_node();
+    let mut checker = TypeChecker::new(parsed.node.unwrap());
+    let typed = match checker.typed_node() {
+        Ok(typed) => typed,
+        Err(error) => {
+            error.prettify();
+            if !watch {
+                return 1;
+            }
+            /* Nothing to generate, wait for the next change */
+            return 0;
+        }
+    };
 
     let context = Context::create();
     let module = context.create_module(name);
@@ -80,6 +94,8 @@ pub fn compile(
             args: vec![],
             ret_type: context.i128_type().into(),
         },
+        captures: vec![],
+        return_type: Type::Int,
     };
 
     if !jit_ {
@@ -93,7 +109,17 @@ pub fn compile(
         );
     }
 
-    match Compiler::init(&context, &builder, &module, &fpm, func).compile_main() {
+    match Compiler::init(
+        &context,
+        &builder,
+        &module,
+        &fpm,
+        func,
+        checker.records,
+        bounds_check,
+    )
+    .compile_main()
+    {
         Ok(_) => {
             if llvm {
                 println!("LLVM IR:\n{}", module.print_to_string().to_string());
@@ -135,6 +161,9 @@ pub fn compile(
         }
         Err(err) => {
             err.prettify();
+            if !watch {
+                return 1;
+            }
         }
     }
 
@@ -55,6 +55,12 @@ struct CmdParams {
      */
     #[structopt(long, short = "l")]
     pub llvm: bool,
+
+    /*
+     * Skip checking array indexes against the length, for release builds (Default: false)
+     */
+    #[structopt(long)]
+    pub no_bounds_check: bool,
 }
 
 /*
@@ -79,6 +85,7 @@ fn main() {
     };
     let watch = cmd_params.watch;
     let spit_ll = cmd_params.llvm;
+    let bounds_check = !cmd_params.no_bounds_check;
 
     /*
      * Compiling to Object File
@@ -92,6 +99,7 @@ fn main() {
             watch,
             out_file.clone(),
             spit_ll,
+            bounds_check,
             false,
         )
     };
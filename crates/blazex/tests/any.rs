@@ -0,0 +1,42 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+mod common;
+
+use common::{output, runtime_error};
+
+#[test]
+fn unboxes_to_the_type_it_holds() {
+    let output = output(
+        "any_unbox",
+        r#"fun wrap(x: any): any { x }
+fun double(n: int): int { n * 2 }
+printf("%d\n", double(wrap(21)));
+val s = wrap("hi");
+if s == "hi" { printf("same\n"); };
+0"#,
+    );
+    assert_eq!(output, "42\nsame\n");
+}
+
+#[test]
+fn unboxing_another_type_fails() {
+    runtime_error(
+        "any_mismatch",
+        r#"fun wrap(x: any): any { x }
+fun double(n: int): int { n * 2 }
+double(wrap("hi"));
+0"#,
+        "Can't cast 'any' holding a 'string' to 'int'",
+    );
+}
@@ -0,0 +1,81 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+mod common;
+
+use common::{output, run};
+
+#[test]
+fn array_methods() {
+    let (output, code) = run(
+        "array_methods",
+        r#"var a = [1, 2, 3];
+a.push(4);
+printf("%d\n", a.pop());
+a.insert(0, 9);
+printf("%d\n", a.remove(1));
+var s = a.slice(1, 3);
+s[0] = 5;
+printf("%d\n", a.len());
+printf("%d\n", a[0]);
+printf("%d\n", s.len());
+printf("%d\n", a[1]);
+0"#,
+        true,
+    );
+    assert_eq!(code, 0);
+    assert_eq!(output, "4\n1\n3\n9\n2\n2\n");
+}
+
+#[test]
+fn unchecked_array_methods_stay_in_bounds() {
+    let (output, code) = run(
+        "unchecked_methods",
+        r#"var a = [1, 2, 3];
+var s = a.slice(0, 100);
+printf("%d\n", s.len());
+s = a.slice(2, 1);
+printf("%d\n", s.len());
+var e = [5];
+printf("%d\n", e.pop());
+printf("%d\n", e.pop());
+printf("%d\n", e.len());
+printf("%d\n", a.remove(7));
+printf("%d\n", a.len());
+a.insert(9, 4);
+printf("%d\n", a[3]);
+0"#,
+        false,
+    );
+    assert_eq!(code, 0);
+    assert_eq!(output, "3\n0\n5\n0\n0\n0\n3\n4\n");
+}
+
+#[test]
+fn assigns_a_value_that_grows_the_array() {
+    let output = output(
+        "array_grow_assign",
+        r#"var a = [1];
+fun grow(): int {
+    for i in 0 to 100 { a.push(i); };
+    7
+}
+a[0] = grow();
+printf("%d\n", a[0]);
+a[1] += grow();
+printf("%d\n", a[1]);
+printf("%d\n", a.len());
+0"#,
+    );
+    assert_eq!(output, "7\n7\n201\n");
+}
@@ -0,0 +1,59 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+mod common;
+
+use common::runtime_error;
+
+#[test]
+fn checked_index_out_of_bounds() {
+    runtime_error(
+        "checked_index",
+        "var a = [1, 2, 3];\nprintf(\"%d\", a[5]);\n0",
+        "index 5 out of bounds for length 3",
+    );
+    runtime_error(
+        "checked_wide_index",
+        "var a = [1];\nprintf(\"%d\", a[4294967296 * 4294967296]);\n0",
+        "index doesn't fit in 64 bits",
+    );
+}
+
+#[test]
+fn checked_array_methods() {
+    runtime_error(
+        "checked_pop",
+        "var a = [1];\na.pop();\na.pop();\n0",
+        "can't pop from an empty array",
+    );
+    runtime_error(
+        "checked_insert",
+        "var a = [1];\na.insert(3, 2);\n0",
+        "insert index 3 out of bounds for length 1",
+    );
+    runtime_error(
+        "checked_remove",
+        "var a = [1];\na.remove(1);\n0",
+        "index 1 out of bounds for length 1",
+    );
+    runtime_error(
+        "checked_slice",
+        "var a = [1, 2];\na.slice(1, 3);\n0",
+        "slice 1..3 out of bounds for length 2",
+    );
+    runtime_error(
+        "checked_reversed_slice",
+        "var a = [1, 2];\na.slice(2, 1);\n0",
+        "slice 2..1 out of bounds for length 2",
+    );
+}
@@ -0,0 +1,37 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+mod common;
+
+use common::output;
+
+#[test]
+fn methods_named_like_generated_functions() {
+    let output = output(
+        "generated_names",
+        r#"class Box {
+    var v = 1;
+    fun(x: int) { soul.v = x; }
+    fun constructor(): int { soul.v + 2 }
+    fun closure(): int { soul.v + 3 }
+}
+fun Box(): int { 40 }
+val b = new Box(5);
+printf("%d\n", b.constructor());
+printf("%d\n", b.closure());
+val f = Box;
+printf("%d\n", f());
+0"#,
+    );
+    assert_eq!(output, "7\n8\n40\n");
+}
@@ -0,0 +1,54 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+mod common;
+
+use common::output;
+
+#[test]
+fn externs_passed_as_values() {
+    let output = output(
+        "extern_values",
+        r#"val p = printf;
+val n = p("hello\n");
+printf("%d\n", n + 1);
+fun apply(f: fun(string): int, s: string): int { f(s) }
+printf("%d\n", apply(printf, "abc\n"));
+0"#,
+    );
+    assert_eq!(output, "hello\n7\nabc\n4\n");
+}
+
+#[test]
+fn closures_capture_their_environment() {
+    let output = output(
+        "closure_captures",
+        r#"fun adder(n: int): fun(int): int {
+    fun(x: int): int { x + n }
+}
+val add5 = adder(5);
+printf("%d\n", add5(10));
+fun twice(f: fun(int): int, x: int): int { f(f(x)) }
+printf("%d\n", twice(add5, 1));
+var base = 100;
+val scale = fun(x: int): int { x * 2 + base };
+printf("%d\n", scale(3));
+fun make(prefix: string): fun(string): int {
+    fun(s: string): int { printf("%s%s\n", prefix, s) }
+}
+val greet = make("hi ");
+greet("there");
+0"#,
+    );
+    assert_eq!(output, "15\n11\n106\nhi there\n");
+}
@@ -0,0 +1,88 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+/*
+ * Helpers shared by the test files, each file only uses some of them
+ */
+#![allow(dead_code)]
+
+use std::env;
+use std::process::{Command, Output};
+
+use blazex::compile;
+
+/*
+ * Compiles, links and runs the program
+ */
+fn execute(name: &str, src: &str, bounds_check: bool) -> Output {
+    let dir = env::temp_dir();
+    let object = dir.join(format!("bzx_{}.o", name));
+    let binary = dir.join(format!("bzx_{}", name));
+
+    let status = compile(
+        format!("{}.bzx", name),
+        String::from(src),
+        true,
+        false,
+        object.to_str().unwrap().to_string(),
+        false,
+        bounds_check,
+        false,
+    );
+    assert_eq!(status, 0, "Failed to compile");
+
+    let linked = Command::new("cc")
+        .arg(&object)
+        .args(&["-no-pie", "-lm", "-o"])
+        .arg(&binary)
+        .status()
+        .unwrap();
+    assert!(linked.success(), "Failed to link");
+
+    Command::new(&binary).output().unwrap()
+}
+
+/*
+ * Compiles and links the program, returning what it printed and its exit code
+ */
+pub fn run(name: &str, src: &str, bounds_check: bool) -> (String, i32) {
+    let output = execute(name, src, bounds_check);
+    (
+        String::from_utf8_lossy(&output.stdout).into_owned(),
+        output.status.code().unwrap_or(-1),
+    )
+}
+
+/*
+ * Runs the program with bounds checks, expecting it to succeed
+ */
+pub fn output(name: &str, src: &str) -> String {
+    let (output, code) = run(name, src, true);
+    assert_eq!(code, 0, "Exited with {}, printing '{}'", code, output);
+    output
+}
+
+/*
+ * Runs the program with bounds checks, expecting it to fail with the message
+ * on stderr
+ */
+pub fn runtime_error(name: &str, src: &str, message: &str) {
+    let output = execute(name, src, true);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(
+        stderr.contains(&format!("Runtime Error: {}", message)),
+        "Unexpected error '{}'",
+        stderr
+    );
+}
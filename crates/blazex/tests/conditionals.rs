@@ -0,0 +1,36 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+mod common;
+
+use common::output;
+
+#[test]
+fn if_gives_the_value_of_its_branch() {
+    let output = output(
+        "if_values",
+        r#"fun sign(n: int): int { if n > 0 { 1 } else if n < 0 { -1 } else { 0 } }
+printf("%d\n", sign(7));
+printf("%d\n", sign(-3));
+printf("%d\n", sign(0));
+var c = true;
+var x = if c { "yes" } else { "no" };
+printf("%s\n", x);
+var f = if not c { 1.5 } else { 2.5 };
+printf("%.1f\n", f);
+val nested = if c { if false { 1 } else { 2 } } else { 3 };
+printf("%d\n", nested);
+0"#,
+    );
+    assert_eq!(output, "1\n-1\n0\nyes\n2.5\n2\n");
+}
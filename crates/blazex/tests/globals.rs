@@ -0,0 +1,39 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+mod common;
+
+use common::output;
+
+#[test]
+fn functions_share_globals() {
+    let output = output(
+        "globals",
+        r#"var count = 0;
+val limit = 3;
+fun bump(): int { count = count + 1; count }
+bump();
+bump();
+printf("%d\n", count);
+fun over(): boolean { count >= limit }
+printf("%d\n", over());
+bump();
+printf("%d\n", over());
+var names = [1, 2];
+fun add(x: int) { names.push(x); }
+add(7);
+printf("%d\n", names.len());
+0"#,
+    );
+    assert_eq!(output, "2\n0\n1\n3\n");
+}
@@ -0,0 +1,31 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+mod common;
+
+use common::output;
+
+#[test]
+fn functions_can_be_called_before_their_definition() {
+    let output = output(
+        "hoisting",
+        r#"printf("%d\n", is_even(10));
+printf("%d\n", is_odd(7));
+printf("%d\n", later(4));
+fun is_even(n: int): boolean { if n == 0 { true } else { is_odd(n - 1) } }
+fun is_odd(n: int): boolean { if n == 0 { false } else { is_even(n - 1) } }
+fun later(n: int): int { n * 3 }
+0"#,
+    );
+    assert_eq!(output, "1\n1\n12\n");
+}
@@ -0,0 +1,88 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+mod common;
+
+use common::output;
+
+#[test]
+fn float_ranges_step_by_one() {
+    let output = output(
+        "float_ranges",
+        r#"for i = 0.0 to 2.0 { printf("%.1f\n", i); }
+for i = 0.5 through 1.5 { printf("%.1f\n", i); }
+0"#,
+    );
+    assert_eq!(output, "0.0\n1.0\n0.5\n1.5\n");
+}
+
+#[test]
+fn break_and_continue_with_labels() {
+    let output = output(
+        "break_continue",
+        r#"var i = 0;
+while true {
+    i = i + 1;
+    if i == 2 { continue; };
+    if i > 4 { break; };
+    printf("%d\n", i);
+};
+outer: for a = 0 to 3 {
+    for b = 0 to 3 {
+        if b > a { continue outer; };
+        if a == 2 { break outer; };
+        printf("%d\n", a * 10 + b);
+    };
+};
+0"#,
+    );
+    assert_eq!(output, "1\n3\n4\n0\n10\n11\n");
+}
+
+#[test]
+fn ranges_check_before_each_iteration() {
+    let output = output(
+        "range_bounds",
+        r#"for i = 0 to 10 step 3 { printf("%d\n", i); }
+for i = 10 through 0 step -5 { printf("%d\n", i); }
+for i = 5 to 5 { printf("%d\n", i); }
+for i = 0 to 10 step 0 { printf("%d\n", i); }
+for i = 3 to 0 { printf("%d\n", i); }
+for i = 1 through 2 { printf("%d\n", i); }
+0"#,
+    );
+    assert_eq!(output, "0\n3\n6\n9\n10\n5\n0\n1\n2\n");
+}
+
+#[test]
+fn for_in_iterates_collections() {
+    let output = output(
+        "for_in",
+        r#"class Counter {
+    var left = 0;
+    fun(n: int) { soul.left = n; }
+    fun has_next(): boolean { soul.left > 0 }
+    fun next(): int { soul.left = soul.left - 1; soul.left }
+}
+for x in [4, 5] { printf("%d\n", x); }
+for c in "ab" { printf("%c\n", c); }
+for i in 0 to 2 { printf("%d\n", i); }
+for i in 7 through 8 { printf("%d\n", i); }
+for n in new Counter(2) { printf("%d\n", n); }
+var total = 0.0;
+for f in [0.5, 1.5] { total = total + f; }
+printf("%.1f\n", total);
+0"#,
+    );
+    assert_eq!(output, "4\n5\na\nb\n0\n1\n7\n8\n1\n0\n2.0\n");
+}
@@ -0,0 +1,33 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+mod common;
+
+use common::output;
+
+#[test]
+fn objects_share_the_registered_layout() {
+    let output = output(
+        "object_layouts",
+        r#"fun first(x) { val o = { a: x, b: 2 }; o.a }
+val p = { a: 1, b: "s" };
+p.a = 4;
+printf("%d\n", p.a);
+printf("%d\n", first(5));
+printf("%.1f\n", first(2.5));
+val q = { a: { c: 3 } };
+printf("%d\n", q.a.c);
+0"#,
+    );
+    assert_eq!(output, "4\n5\n2.5\n3\n");
+}
@@ -0,0 +1,122 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+mod common;
+
+use common::output;
+
+#[test]
+fn shift_amounts_wrap_around() {
+    let output = output(
+        "shift_amounts",
+        r#"printf("%d\n", 1 << 130);
+printf("%d\n", 8 >> -127);
+printf("%d\n", -16 >>> 124);
+var x = 1;
+x <<= 131;
+printf("%d\n", x);
+0"#,
+    );
+    assert_eq!(output, "4\n4\n15\n8\n");
+}
+
+#[test]
+fn and_or_short_circuit() {
+    let output = output(
+        "short_circuit",
+        r#"var calls = 0;
+fun hit(b: boolean): boolean { calls += 1; b }
+var x = 0;
+if x != 0 and 10 / x > 1 { printf("big\n"); } else { printf("safe\n"); };
+val a = hit(false) and hit(true);
+printf("%d\n", calls);
+val o = hit(true) or hit(true);
+printf("%d\n", calls);
+val c = hit(true) and hit(false);
+printf("%d\n", calls);
+if a or c { printf("wrong\n"); };
+if o and not a { printf("right\n"); };
+0"#,
+    );
+    assert_eq!(output, "safe\n1\n2\n4\nright\n");
+}
+
+#[test]
+fn bitwise_operators() {
+    let output = output(
+        "bitwise",
+        r#"printf("%d\n", 12 & 10);
+printf("%d\n", 12 | 3);
+printf("%d\n", 12 xor 10);
+printf("%d\n", ~5);
+printf("%d\n", -16 >> 2);
+printf("%d\n", 1 + 2 << 3);
+printf("%d\n", 6 & 3 | 8);
+var f = 5;
+f &= 4;
+printf("%d\n", f);
+f |= 3;
+printf("%d\n", f);
+f xor= 1;
+printf("%d\n", f);
+f >>>= 1;
+printf("%d\n", f);
+0"#,
+    );
+    assert_eq!(output, "8\n15\n6\n-6\n-4\n24\n10\n4\n7\n6\n3\n");
+}
+
+#[test]
+fn unary_operators_on_variables() {
+    let output = output(
+        "unary",
+        r#"var x = 5;
+printf("%d\n", -x);
+printf("%d\n", +x);
+var f = 2.5;
+printf("%.1f\n", -f);
+var b = false;
+if not b { printf("not\n"); };
+if !b { printf("bang\n"); };
+if !(x > 3) { printf("wrong\n"); };
+printf("%d\n", -(-x));
+0"#,
+    );
+    assert_eq!(output, "-5\n5\n-2.5\nnot\nbang\n5\n");
+}
+
+#[test]
+fn equality_compares_values() {
+    let output = output(
+        "equality",
+        r#"var a = 3;
+var b = 3;
+if a == b { printf("ints\n"); };
+if 1.5 != 2.5 { printf("floats\n"); };
+if 'c' == 'c' { printf("chars\n"); };
+if true != false { printf("booleans\n"); };
+var s = "hel";
+if "hello" == "hello" { printf("strings\n"); };
+if s != "hello" { printf("prefix\n"); };
+if [1, 2] == [1, 2] { printf("arrays\n"); };
+if [1, 2] != [1, 2, 3] { printf("lengths\n"); };
+if { x: 1, y: "a" } == { x: 1, y: "a" } { printf("objects\n"); };
+if { x: 1, y: "a" } != { x: 1, y: "b" } { printf("fields\n"); };
+if [[1], [2]] == [[1], [2]] { printf("nested\n"); };
+0"#,
+    );
+    assert_eq!(
+        output,
+        "ints\nfloats\nchars\nbooleans\nstrings\nprefix\narrays\nlengths\nobjects\nfields\nnested\n"
+    );
+}
@@ -28,9 +28,13 @@ pub fn get_keywords() -> Vec<String> {
         string("if"),
         string("else"),
         string("for"),
+        string("in"),
         string("to"),
+        string("through"),
         string("step"),
         string("while"),
+        string("break"),
+        string("continue"),
         string("fun"),
         string("return"),
         string("class"),
@@ -152,6 +156,7 @@ impl Lexer {
                 ':' => Tokens::Colon,
                 ',' => Tokens::Comma,
                 '.' => Tokens::Dot,
+                '~' => Tokens::BitwiseNot,
                 _ => Tokens::Unknown,
             };
 
@@ -164,6 +169,7 @@ impl Lexer {
                         tokens.push(self.make_arith_ops(Tokens::Multiply, Tokens::MultiplyEquals))
                     }
                     '/' => tokens.push(self.make_arith_ops(Tokens::Divide, Tokens::DivideEquals)),
+                    '%' => tokens.push(self.make_arith_ops(Tokens::Modulo, Tokens::ModuloEquals)),
                     '^' => tokens.push(self.make_arith_ops(Tokens::Power, Tokens::PowerEquals)),
                     '@' => self.skip_comment(),
                     '"' => tokens.push(self.make_string()),
@@ -180,24 +186,8 @@ impl Lexer {
                             }
                         };
                     }
-                    '|' => {
-                        let result = self.make_or();
-                        match result {
-                            Ok(token) => tokens.push(token),
-                            Err(e) => {
-                                return Err(e);
-                            }
-                        };
-                    }
-                    '&' => {
-                        let result = self.make_and();
-                        match result {
-                            Ok(token) => tokens.push(token),
-                            Err(e) => {
-                                return Err(e);
-                            }
-                        };
-                    }
+                    '|' => tokens.push(self.make_or()),
+                    '&' => tokens.push(self.make_and()),
                     _ => {
                         let no = self.current_char.unwrap().to_digit(36);
                         if no.is_some() {
@@ -254,7 +244,18 @@ impl Lexer {
     fn make_arith_ops(&mut self, no_eq: Tokens, eq: Tokens) -> Token {
         let start = self.position.clone();
         self.advance();
+        self.make_compound_ops(no_eq, eq, start)
+    }
 
+    /*
+     * Makes the operator which started at `start` or its '=' form if one follows
+     */
+    pub(crate) fn make_compound_ops(
+        &mut self,
+        no_eq: Tokens,
+        eq: Tokens,
+        start: Position,
+    ) -> Token {
         if self.current_char.unwrap_or(' ') == '=' {
             self.advance();
             return Token::new(eq, start, self.position, DynType::None);
@@ -280,6 +281,10 @@ impl Lexer {
             self.advance();
         }
 
+        if identifier == "xor" {
+            return self.make_compound_ops(Tokens::BitwiseXor, Tokens::BitwiseXorEquals, start);
+        }
+
         let identifier_type = if get_keywords().contains(&identifier) {
             Tokens::Keyword
         } else if identifier == "true".to_string() || identifier == "false".to_string() {
@@ -1,4 +1,4 @@
-use bzxc_shared::{DynType, Error, Token, Tokens};
+use bzxc_shared::{DynType, Token, Tokens};
 
 use crate::Lexer;
 
@@ -24,13 +24,19 @@ impl Lexer {
     }
 
     /*
-     * Makes a LESS_THAN or LESS_THAN_EQUALS Token
+     * Makes a LESS_THAN, LESS_THAN_EQUALS, LEFT_SHIFT or LEFT_SHIFT_EQUALS Token
      */
     pub(crate) fn make_less_than(&mut self) -> Token {
         let start = self.position.clone();
         self.advance();
 
+        if self.current_char.unwrap_or(' ') == '<' {
+            self.advance();
+            return self.make_compound_ops(Tokens::LeftShift, Tokens::LeftShiftEquals, start);
+        }
+
         if self.current_char.unwrap_or(' ') == '=' {
+            self.advance();
             return Token::new(
                 Tokens::LessThanEquals,
                 start,
@@ -48,13 +54,43 @@ impl Lexer {
     }
 
     /*
-     * Makes a GREATER_THAN or GREATER_THAN_EQUALS Token
+     * Makes a GREATER_THAN, GREATER_THAN_EQUALS or a (LOGICAL_)RIGHT_SHIFT(_EQUALS) Token
      */
     pub(crate) fn make_greater_than(&mut self) -> Token {
         let start = self.position.clone();
         self.advance();
 
+        if self.current_char.unwrap_or(' ') == '>' {
+            self.advance();
+            if self.current_char.unwrap_or(' ') == '>' {
+                self.advance();
+                return self.make_compound_ops(
+                    Tokens::LogicalRightShift,
+                    Tokens::LogicalRightShiftEquals,
+                    start,
+                );
+            }
+
+            if self.current_char.unwrap_or(' ') == '=' {
+                self.advance();
+                return Token::new(
+                    Tokens::RightShiftEquals,
+                    start,
+                    self.position.clone(),
+                    DynType::None,
+                );
+            }
+
+            return Token::new(
+                Tokens::RightShift,
+                start,
+                self.position.clone(),
+                DynType::None,
+            );
+        }
+
         if self.current_char.unwrap_or(' ') == '=' {
+            self.advance();
             return Token::new(
                 Tokens::GreaterThanEquals,
                 start,
@@ -97,52 +133,42 @@ impl Lexer {
     }
 
     /*
-     * Makes a NOT Token
+     * Makes a OR, BITWISE_OR or BITWISE_OR_EQUALS Token
      */
-    pub(crate) fn make_or(&mut self) -> Result<Token, Error> {
+    pub(crate) fn make_or(&mut self) -> Token {
         let start = self.position.clone();
         self.advance();
 
         if self.current_char.unwrap_or(' ') == '|' {
             self.advance();
-            return Ok(Token::new(
+            return Token::new(
                 Tokens::Keyword,
                 start,
                 self.position.clone(),
                 DynType::String("or".to_string()),
-            ));
+            );
         }
 
-        Err(Error::new(
-            "Expected Character",
-            start,
-            self.position.clone(),
-            "Expected one more '|'",
-        ))
+        self.make_compound_ops(Tokens::BitwiseOr, Tokens::BitwiseOrEquals, start)
     }
 
     /*
-     * Makes a AND Token
+     * Makes a AND, BITWISE_AND or BITWISE_AND_EQUALS Token
      */
-    pub(crate) fn make_and(&mut self) -> Result<Token, Error> {
+    pub(crate) fn make_and(&mut self) -> Token {
         let start = self.position.clone();
         self.advance();
 
         if self.current_char.unwrap_or(' ') == '&' {
             self.advance();
-            return Ok(Token::new(
+            return Token::new(
                 Tokens::Keyword,
                 start,
                 self.position.clone(),
                 DynType::String("and".to_string()),
-            ));
+            );
         }
 
-        Err(Error::new(
-            "Expected Character",
-            start,
-            self.position.clone(),
-            "Expected one more '&'",
-        ))
+        self.make_compound_ops(Tokens::BitwiseAnd, Tokens::BitwiseAndEquals, start)
     }
 }
@@ -0,0 +1,242 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+use bzxc_llvm_wrapper::{
+    module::Linkage,
+    types::{BasicType, BasicTypeEnum, FunctionType, PointerType},
+    values::{BasicValueEnum, FunctionValue, PointerValue},
+    AddressSpace, IntPredicate,
+};
+use bzxc_shared::{try_any_to_basic, Error, Position, Type};
+
+use crate::Compiler;
+
+impl<'a, 'ctx> Compiler<'a, 'ctx> {
+    /*
+     * Type of values of type 'any', a pointer to the tag of their box
+     */
+    pub(crate) fn any_type(&self) -> PointerType<'ctx> {
+        self.any.ptr_type(AddressSpace::Generic)
+    }
+
+    /*
+     * Boxes the value of the type into a 'any' or checks and unboxes a 'any'
+     * into the type, any other value is already of the type
+     */
+    pub(crate) fn cast(
+        &mut self,
+        value: BasicValueEnum<'ctx>,
+        from: &Type,
+        to: &Type,
+        pos: (Position, Position),
+    ) -> Result<BasicValueEnum<'ctx>, Error> {
+        match (from, to) {
+            (Type::Any, Type::Any) => Ok(value),
+            (from, Type::Any) => self.box_any(value, from, pos),
+            (Type::Any, to) => self.unbox_any(value.into_pointer_value(), to, pos),
+            _ => Ok(value),
+        }
+    }
+
+    fn box_any(
+        &mut self,
+        value: BasicValueEnum<'ctx>,
+        typee: &Type,
+        pos: (Position, Position),
+    ) -> Result<BasicValueEnum<'ctx>, Error> {
+        let name =
+            type_name(typee).ok_or_else(|| self.error(pos, "Can't box the value into 'any'"))?;
+        let box_type = self.box_type(value.get_type());
+
+        let ptr = self
+            .builder
+            .build_malloc(box_type, "any_box")
+            .map_err(|_| self.error(pos, "Can't allocate the 'any' box"))?;
+        let tag_ptr = self.builder.build_struct_gep(ptr, 0, "any_tag").unwrap();
+        let tag = self.type_tag(name);
+        self.builder.build_store(tag_ptr, tag);
+        let value_ptr = self.builder.build_struct_gep(ptr, 1, "any_value").unwrap();
+        self.builder.build_store(value_ptr, value);
+
+        Ok(self
+            .builder
+            .build_pointer_cast(ptr, self.any_type(), "any")
+            .into())
+    }
+
+    fn unbox_any(
+        &mut self,
+        value: PointerValue<'ctx>,
+        typee: &Type,
+        pos: (Position, Position),
+    ) -> Result<BasicValueEnum<'ctx>, Error> {
+        let name =
+            type_name(typee).ok_or_else(|| self.error(pos, "Can't cast 'any' to the type"))?;
+
+        let tag_ptr = self.builder.build_struct_gep(value, 0, "any_tag").unwrap();
+        let tag = self.builder.build_load(tag_ptr, "tag").into_pointer_value();
+        let expected = self.type_tag(name);
+        let i64_type = self.context.i64_type();
+        let matches = self.builder.build_int_compare(
+            IntPredicate::EQ,
+            self.builder.build_ptr_to_int(tag, i64_type, "tag_int"),
+            self.builder
+                .build_ptr_to_int(expected, i64_type, "expected_int"),
+            "tag_matches",
+        );
+
+        let parent = self.fn_value();
+        let fail_block = self.context.append_basic_block(parent, "cast_failed");
+        let ok_block = self.context.append_basic_block(parent, "cast");
+        self.builder
+            .build_conditional_branch(matches, ok_block, fail_block);
+
+        self.builder.position_at_end(fail_block);
+        self.runtime_error(
+            &format!("Can't cast 'any' holding a '%s' to '{}'", name),
+            &[tag.into()],
+            pos,
+        );
+
+        self.builder.position_at_end(ok_block);
+        let ptr = self.builder.build_pointer_cast(
+            value,
+            self.box_type(try_any_to_basic(self.llvm_type(typee)))
+                .ptr_type(AddressSpace::Generic),
+            "any_box",
+        );
+        let value_ptr = self.builder.build_struct_gep(ptr, 1, "any_value").unwrap();
+
+        Ok(self.builder.build_load(value_ptr, "unboxed"))
+    }
+
+    fn box_type(&self, typee: BasicTypeEnum<'ctx>) -> BasicTypeEnum<'ctx> {
+        self.context
+            .struct_type(
+                &[
+                    self.context
+                        .i8_type()
+                        .ptr_type(AddressSpace::Generic)
+                        .into(),
+                    typee,
+                ],
+                false,
+            )
+            .into()
+    }
+
+    /*
+     * The runtime tag of a boxed value, a pointer to the name of its type
+     */
+    fn type_tag(&self, name: &str) -> PointerValue<'ctx> {
+        let global_name = format!("any.{}", name);
+        let global = match self.module.get_global(&global_name) {
+            Some(global) => global,
+            None => self.builder.build_global_string_ptr(name, &global_name),
+        };
+
+        self.builder.build_pointer_cast(
+            global.as_pointer_value(),
+            self.context.i8_type().ptr_type(AddressSpace::Generic),
+            "tag",
+        )
+    }
+
+    /*
+     * Prints the printf style message with where it happened to stderr and
+     * exits, the block is left terminated
+     */
+    pub(crate) fn runtime_error(
+        &self,
+        message: &str,
+        args: &[BasicValueEnum<'ctx>],
+        pos: (Position, Position),
+    ) {
+        let message = self
+            .builder
+            .build_global_string_ptr(
+                &format!(
+                    "{}: Runtime Error: {}\n",
+                    location(pos.0).replace('%', "%%"),
+                    message
+                ),
+                "runtime_error",
+            )
+            .as_pointer_value();
+        let dprintf = self.runtime_fn(
+            "dprintf",
+            self.context.i32_type().fn_type(
+                &[
+                    self.context.i32_type().into(),
+                    self.context
+                        .i8_type()
+                        .ptr_type(AddressSpace::Generic)
+                        .into(),
+                ],
+                true,
+            ),
+        );
+        let stderr = self.context.i32_type().const_int(2, false);
+        let mut dprintf_args = vec![stderr.into(), message.into()];
+        dprintf_args.extend_from_slice(args);
+        self.builder
+            .build_call(dprintf, &dprintf_args[..], "dprintf")
+            .unwrap();
+        let exit = self.runtime_fn(
+            "exit",
+            self.context
+                .void_type()
+                .fn_type(&[self.context.i32_type().into()], false),
+        );
+        self.builder
+            .build_call(
+                exit,
+                &[self.context.i32_type().const_int(1, false).into()],
+                "exit",
+            )
+            .unwrap();
+        self.builder.build_unreachable();
+    }
+
+    pub(crate) fn runtime_fn(&self, name: &str, typee: FunctionType<'ctx>) -> FunctionValue<'ctx> {
+        self.get_function(name).unwrap_or_else(|| {
+            self.module
+                .add_function(name, typee, Some(Linkage::External))
+        })
+    }
+}
+
+/*
+* Name of the type as seen by the type checker, if it can be boxed
+*/
+fn type_name(typee: &Type) -> Option<&'static str> {
+    match typee {
+        Type::Int => Some("int"),
+        Type::Char => Some("char"),
+        Type::Boolean => Some("boolean"),
+        Type::Float => Some("float"),
+        Type::String => Some("string"),
+        _ => None,
+    }
+}
+
+/*
+* 'file:line:column' of the position
+*/
+fn location(pos: Position) -> String {
+    let before = &pos.file_content[..pos.index.min(pos.file_content.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map(|x| x + 1).unwrap_or(0) + 1;
+
+    format!("{}:{}:{}", pos.file_name, line, column)
+}
@@ -11,53 +11,55 @@
  * limitations under the License.
 */
 
-use bzxc_llvm_wrapper::{types::BasicTypeEnum, values::BasicValueEnum};
-use bzxc_shared::{Error, Node, Position};
+use bzxc_llvm_wrapper::{
+    types::{BasicType, BasicTypeEnum},
+    values::{BasicValueEnum, FunctionValue, IntValue, PointerValue},
+    AddressSpace, IntPredicate,
+};
+use bzxc_shared::{try_any_to_basic, Error, Position, Token, Type, TypedNode};
 
 use crate::Compiler;
 
 impl<'a, 'ctx> Compiler<'a, 'ctx> {
     pub(crate) fn array_decl(
         &mut self,
-        element_nodes: Vec<Node>,
+        element_nodes: Vec<TypedNode>,
+        typee: Type,
         pos: (Position, Position),
     ) -> Result<BasicValueEnum<'ctx>, Error> {
-        if element_nodes.is_empty() {
-            return Ok(self.context.i128_type().array_type(0).const_zero().into());
-        }
-
-        let ty = self
-            .compile_node(element_nodes.get(0).unwrap().clone())?
-            .get_type();
-
-        let size = element_nodes.len() as u32;
-        let arr = match ty {
-            BasicTypeEnum::ArrayType(x) => x.array_type(size),
-            BasicTypeEnum::FloatType(x) => x.array_type(size),
-            BasicTypeEnum::IntType(x) => x.array_type(size),
-            BasicTypeEnum::PointerType(x) => x.array_type(size),
-            BasicTypeEnum::StructType(x) => x.array_type(size),
-            BasicTypeEnum::VectorType(x) => x.array_type(size),
+        let elem_type = match typee {
+            Type::Array(elem) => *elem,
+            _ => return Err(self.error(pos, "Expected a 'array'")),
         };
-
-        let array_alloca = self.builder.build_alloca(arr, "array_alloca");
-        let mut array = self
+        let elem = self.elem_type(&elem_type, pos)?;
+        let len = self
+            .context
+            .i64_type()
+            .const_int(element_nodes.len() as u64, false);
+        let array = self
             .builder
-            .build_load(array_alloca, "array_load")
-            .into_array_value();
-
-        for (i, k) in element_nodes.iter().enumerate() {
-            let elem = self.compile_node(k.clone())?;
+            .build_call(
+                self.array_new(),
+                &[len.into(), self.elem_size(elem).into()],
+                "array",
+            )
+            .unwrap()
+            .try_as_basic_value()
+            .left()
+            .unwrap()
+            .into_pointer_value();
 
-            if elem.get_type() != ty {
+        for (i, element) in element_nodes.into_iter().enumerate() {
+            let (elem_pos, val_type) = (element.pos, element.typee.clone());
+            let val = self.compile_node(element)?;
+            let val = self.cast(val, &val_type, &elem_type, elem_pos)?;
+            if val.get_type() != elem {
                 return Err(self.error(pos, "Arrays cannot be of multiple types"));
             }
 
-            array = self
-                .builder
-                .build_insert_value(array, elem, i as u32, "load_array")
-                .unwrap()
-                .into_array_value();
+            let idx = self.context.i64_type().const_int(i as u64, false);
+            let ptr = self.element(array, elem, idx);
+            self.builder.build_store(ptr, val);
         }
 
         Ok(array.into())
@@ -65,35 +67,357 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
 
     pub(crate) fn array_access(
         &mut self,
-        array: Node,
-        index: Node,
+        array: TypedNode,
+        index: TypedNode,
+        elem_type: Type,
+        pos: (Position, Position),
+    ) -> Result<BasicValueEnum<'ctx>, Error> {
+        let elem_ptr = self.element_ptr(array, index, &elem_type, pos)?;
+        Ok(self.builder.build_load(elem_ptr, "array_elem"))
+    }
+
+    pub(crate) fn array_edit(
+        &mut self,
+        array: TypedNode,
+        index: TypedNode,
+        typee: Token,
+        new_val: TypedNode,
+        elem_type: Type,
+        pos: (Position, Position),
+    ) -> Result<BasicValueEnum<'ctx>, Error> {
+        /* The value can grow the array and move its elements, so it goes first */
+        let val_type = new_val.typee.clone();
+        let val = self.compile_node(new_val)?;
+        let elem_ptr = self.element_ptr(array, index, &elem_type, pos)?;
+        self.assign(elem_ptr, &typee, val, &val_type, &elem_type, pos)
+    }
+
+    /*
+     * Calls one of the built-in array methods, the checker has already made
+     * sure the arguments fit. A index outside the array is a runtime error,
+     * or with '--no-bounds-check' never touches memory outside it: 'pop' and
+     * 'remove' give a zero value and leave the array as it is, while 'insert'
+     * appends
+     */
+    pub(crate) fn array_method(
+        &mut self,
+        object: TypedNode,
+        property: Token,
+        args: Vec<TypedNode>,
         pos: (Position, Position),
     ) -> Result<BasicValueEnum<'ctx>, Error> {
-        let array = self.compile_node(array)?;
-        if !array.is_array_value() {
-            return Err(self.error(pos, "Expected a 'array'"));
+        let elem_type = match &object.typee {
+            Type::Array(elem) => *elem.clone(),
+            _ => return Err(self.error(pos, "Expected a 'array'")),
+        };
+        let elem = self.elem_type(&elem_type, pos)?;
+        let array = self.compile_node(object)?.into_pointer_value();
+        let size = self.elem_size(elem);
+        let i64_type = self.context.i64_type();
+        let one = i64_type.const_int(1, false);
+        let void = self.context.i128_type().const_zero().into();
+
+        let mut vals = Vec::with_capacity(args.len());
+        for arg in args {
+            let (arg_pos, arg_type) = (arg.pos, arg.typee.clone());
+            let val = self.compile_node(arg)?;
+            vals.push((val, arg_type, arg_pos));
         }
+
+        let len = self.array_len(array);
+        let zero = i64_type.const_zero();
+        Ok(match property.value.into_string().as_str() {
+            "len" => self
+                .builder
+                .build_int_s_extend(len, self.context.i128_type(), "len")
+                .into(),
+            "push" => {
+                let (val, val_type, val_pos) = vals.remove(0);
+                let val = self.cast(val, &val_type, &elem_type, val_pos)?;
+                let new_len = self.builder.build_int_add(len, one, "new_len");
+                self.call_runtime(
+                    self.array_reserve(),
+                    &[array.into(), new_len.into(), size.into()],
+                );
+                let ptr = self.element(array, elem, len);
+                self.builder.build_store(ptr, val);
+                self.set_len(array, new_len);
+                void
+            }
+            "pop" => {
+                let empty = self
+                    .builder
+                    .build_int_compare(IntPredicate::EQ, len, zero, "empty");
+                self.guard(
+                    self.builder.build_not(empty, "has_last"),
+                    "can't pop from an empty array",
+                    &[],
+                    pos,
+                );
+                let last = self.builder.build_int_sub(len, one, "last");
+                let new_len = self
+                    .builder
+                    .build_select(empty, zero, last, "new_len")
+                    .into_int_value();
+                let ptr = self.element(array, elem, new_len);
+                let val = self.builder.build_load(ptr, "popped");
+                self.set_len(array, new_len);
+                self.builder
+                    .build_select(empty, elem.const_zero(), val, "popped")
+            }
+            "insert" => {
+                let valid = self.in_range(vals[0].0, len, true);
+                let at = self.index(vals[0].0, pos);
+                self.guard(
+                    valid,
+                    "insert index %lld out of bounds for length %lld",
+                    &[at.into(), len.into()],
+                    pos,
+                );
+                let at = self
+                    .builder
+                    .build_select(valid, at, len, "at")
+                    .into_int_value();
+                let (val, val_type, val_pos) = vals.remove(1);
+                let val = self.cast(val, &val_type, &elem_type, val_pos)?;
+                let new_len = self.builder.build_int_add(len, one, "new_len");
+                self.call_runtime(
+                    self.array_reserve(),
+                    &[array.into(), new_len.into(), size.into()],
+                );
+                let to = self.builder.build_int_add(at, one, "to");
+                self.call_runtime(
+                    self.array_shift(),
+                    &[array.into(), at.into(), to.into(), size.into()],
+                );
+                let ptr = self.element(array, elem, at);
+                self.builder.build_store(ptr, val);
+                self.set_len(array, new_len);
+                void
+            }
+            "remove" => {
+                /* Out of range, nothing is moved as the shift starts at the end */
+                let valid = self.in_range(vals[0].0, len, false);
+                let at = self.index(vals[0].0, pos);
+                self.guard(
+                    valid,
+                    "index %lld out of bounds for length %lld",
+                    &[at.into(), len.into()],
+                    pos,
+                );
+                let at = self
+                    .builder
+                    .build_select(valid, at, zero, "at")
+                    .into_int_value();
+                let ptr = self.element(array, elem, at);
+                let val = self.builder.build_load(ptr, "removed");
+                let next = self.builder.build_int_add(at, one, "next");
+                let from = self
+                    .builder
+                    .build_select(valid, next, len, "from")
+                    .into_int_value();
+                let to = self
+                    .builder
+                    .build_select(valid, at, len, "to")
+                    .into_int_value();
+                self.call_runtime(
+                    self.array_shift(),
+                    &[array.into(), from.into(), to.into(), size.into()],
+                );
+                let last = self.builder.build_int_sub(len, one, "last");
+                let new_len = self
+                    .builder
+                    .build_select(valid, last, len, "new_len")
+                    .into_int_value();
+                self.set_len(array, new_len);
+                self.builder
+                    .build_select(valid, val, elem.const_zero(), "removed")
+            }
+            "slice" => {
+                /* 'start' is in the array and 'end' between it and the length */
+                let start_valid = self.in_range(vals[0].0, len, true);
+                let (wide_start, wide_end) =
+                    (vals[0].0.into_int_value(), vals[1].0.into_int_value());
+                let ordered = self.builder.build_int_compare(
+                    IntPredicate::SLE,
+                    wide_start,
+                    wide_end,
+                    "ordered",
+                );
+                let end_valid = self.in_range(vals[1].0, len, true);
+                let valid = self.builder.build_and(start_valid, ordered, "valid");
+                let valid = self.builder.build_and(valid, end_valid, "valid");
+                let start = self.index(vals[0].0, pos);
+                let end = self.index(vals[1].0, pos);
+                self.guard(
+                    valid,
+                    "slice %lld..%lld out of bounds for length %lld",
+                    &[start.into(), end.into(), len.into()],
+                    pos,
+                );
+                self.call_runtime(
+                    self.array_slice(),
+                    &[array.into(), start.into(), end.into(), size.into()],
+                )
+                .unwrap()
+            }
+            _ => return Err(self.error(pos, "Unknown array method")),
+        })
+    }
+
+    /*
+     * Pointer to a element inside the array's heap storage, so writes through
+     * it are seen by everyone holding the array
+     */
+    pub(crate) fn element_ptr(
+        &mut self,
+        array: TypedNode,
+        index: TypedNode,
+        elem_type: &Type,
+        pos: (Position, Position),
+    ) -> Result<PointerValue<'ctx>, Error> {
+        let elem = self.elem_type(elem_type, pos)?;
+        let array = self.compile_node(array)?.into_pointer_value();
+
+        let (index_pos, index_type) = (index.pos, index.typee.clone());
         let idx = self.compile_node(index)?;
-        if !idx.is_int_value() {
-            return Err(self.error(pos, "Expected a index"));
+        let idx = self.cast(idx, &index_type, &Type::Int, index_pos)?;
+
+        let len = self.array_len(array);
+        let in_range = self.in_range(idx, len, false);
+        let idx = self.index(idx, pos);
+        self.guard(
+            in_range,
+            "index %lld out of bounds for length %lld",
+            &[idx.into(), len.into()],
+            pos,
+        );
+        Ok(self.element(array, elem, idx))
+    }
+
+    /*
+     * Aborts with the message unless the condition holds, if bounds checks
+     * weren't turned off
+     */
+    fn guard(
+        &self,
+        condition: IntValue<'ctx>,
+        message: &str,
+        args: &[BasicValueEnum<'ctx>],
+        pos: (Position, Position),
+    ) {
+        if !self.bounds_check {
+            return;
         }
 
-        let arr = array.into_array_value();
-        let array_alloca = self.builder.build_alloca(arr.get_type(), "arr_alloc");
-        self.builder.build_store(array_alloca, arr);
-
-        let array_elem_ptr = unsafe {
-            self.builder.build_gep(
-                array_alloca,
-                &[
-                    self.context.i32_type().const_int(0, false),
-                    idx.into_int_value(),
-                ],
-                "get_array_elem_ptr",
-            )
+        let parent = self.fn_value();
+        let fail_block = self.context.append_basic_block(parent, "out_of_bounds");
+        let ok_block = self.context.append_basic_block(parent, "in_bounds");
+        self.builder
+            .build_conditional_branch(condition, ok_block, fail_block);
+
+        self.builder.position_at_end(fail_block);
+        self.runtime_error(message, args, pos);
+
+        self.builder.position_at_end(ok_block);
+    }
+
+    pub(crate) fn array_len(&self, array: PointerValue<'ctx>) -> IntValue<'ctx> {
+        self.builder
+            .build_load(self.array_field(array, 0), "len")
+            .into_int_value()
+    }
+
+    pub(crate) fn element(
+        &self,
+        array: PointerValue<'ctx>,
+        elem: BasicTypeEnum<'ctx>,
+        idx: IntValue<'ctx>,
+    ) -> PointerValue<'ctx> {
+        let data = self
+            .builder
+            .build_load(self.array_field(array, 2), "data")
+            .into_pointer_value();
+        let data =
+            self.builder
+                .build_pointer_cast(data, elem.ptr_type(AddressSpace::Generic), "elems");
+        unsafe { self.builder.build_gep(data, &[idx], "elem_ptr") }
+    }
+
+    pub(crate) fn elem_type(
+        &self,
+        typee: &Type,
+        pos: (Position, Position),
+    ) -> Result<BasicTypeEnum<'ctx>, Error> {
+        let elem = self.llvm_type(typee);
+        if elem.is_void_type() || elem.is_function_type() {
+            return Err(self.error(pos, "Arrays can't hold this type"));
+        }
+        Ok(try_any_to_basic(elem))
+    }
+
+    fn set_len(&self, array: PointerValue<'ctx>, len: IntValue<'ctx>) {
+        self.builder.build_store(self.array_field(array, 0), len);
+    }
+
+    fn elem_size(&self, elem: BasicTypeEnum<'ctx>) -> IntValue<'ctx> {
+        elem.size_of().unwrap()
+    }
+
+    /*
+     * Whether the 'int' index is inside the array, or at its end too when
+     * inclusive. Compared before truncating so huge indexes can't wrap around
+     */
+    fn in_range(
+        &self,
+        idx: BasicValueEnum<'ctx>,
+        len: IntValue<'ctx>,
+        inclusive: bool,
+    ) -> IntValue<'ctx> {
+        let idx = idx.into_int_value();
+        let len = self
+            .builder
+            .build_int_z_extend(len, idx.get_type(), "wide_len");
+        /* A negative index is a huge unsigned one */
+        let predicate = if inclusive {
+            IntPredicate::ULE
+        } else {
+            IntPredicate::ULT
         };
-        let array_elem = self.builder.build_load(array_elem_ptr, "array_elem");
+        self.builder
+            .build_int_compare(predicate, idx, len, "in_range")
+    }
+
+    /*
+     * Truncates the 'int' index to the width of the array's length
+     */
+    fn index(&self, idx: BasicValueEnum<'ctx>, pos: (Position, Position)) -> IntValue<'ctx> {
+        let wide = idx.into_int_value();
+        let idx = self
+            .builder
+            .build_int_truncate_or_bit_cast(wide, self.context.i64_type(), "idx");
+
+        /* So the error doesn't show the truncated index */
+        let extended = self
+            .builder
+            .build_int_s_extend(idx, wide.get_type(), "extended");
+        let fits = self
+            .builder
+            .build_int_compare(IntPredicate::EQ, extended, wide, "fits");
+        self.guard(fits, "index doesn't fit in 64 bits", &[], pos);
+
+        idx
+    }
 
-        Ok(array_elem)
+    fn call_runtime(
+        &self,
+        function: FunctionValue<'ctx>,
+        args: &[BasicValueEnum<'ctx>],
+    ) -> Option<BasicValueEnum<'ctx>> {
+        self.builder
+            .build_call(function, args, "runtime")
+            .unwrap()
+            .try_as_basic_value()
+            .left()
     }
 }
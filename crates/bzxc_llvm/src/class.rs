@@ -11,29 +11,216 @@
  * limitations under the License.
 */
 
-use bzxc_shared::{Error, Node, Position, Token, Type};
-use bzxc_llvm_wrapper::values::BasicValueEnum;
+use bzxc_llvm_wrapper::{
+    types::{BasicTypeEnum, StructType},
+    values::{BasicValueEnum, FunctionValue},
+    AddressSpace,
+};
+use bzxc_shared::{Error, Position, Record, Token, Type, TypedMethod, TypedNode};
 
-use crate::Compiler;
+use crate::{Compiler, Prototype};
 
 impl<'a, 'ctx> Compiler<'a, 'ctx> {
     pub(crate) fn class_decl(
-        &self,
-        constructor: Option<(Vec<Token>, Node)>,
-        properties: Vec<(Token, Node)>,
+        &mut self,
+        constructor: Option<(Vec<(Token, Type)>, TypedNode)>,
+        properties: Vec<(Token, TypedNode)>,
         name: Token,
-        methods: Vec<(Token, Vec<Token>, Node, Type)>,
+        methods: Vec<TypedMethod>,
         pos: (Position, Position),
     ) -> Result<BasicValueEnum<'ctx>, Error> {
-        Err(self.error(pos, "Node can't be compiled"))
+        let class_name = name.value.into_string();
+        let struct_type = match self.classes.get(&class_name) {
+            Some(class) if class.is_opaque() => *class,
+            Some(_) => return Err(self.error(pos, "Class already defined")),
+            None => self.class_type(&class_name),
+        };
+        let soul_type: BasicTypeEnum = struct_type.ptr_type(AddressSpace::Generic).into();
+
+        /* Methods reach the properties through the record of the class */
+        let record = self
+            .records
+            .get(&class_name)
+            .cloned()
+            .ok_or(self.error(pos, "Class not found"))?;
+
+        /*
+         * Methods are '<class>.<method>', the functions made for the class use
+         * '::' so no method name can collide with them
+         */
+        let mut funcs = vec![];
+        if let Some((arg_tokens, body_node)) = constructor {
+            self.constructors.insert(
+                class_name.clone(),
+                arg_tokens.iter().map(|x| x.1.clone()).collect(),
+            );
+            let mut func = self.to_func_with_proto(arg_tokens, body_node, None, Type::Void)?;
+            func.prototype.name = Some(format!("{}::constructor", class_name));
+            func.prototype
+                .args
+                .insert(0, (String::from("soul"), soul_type));
+            funcs.push((self.compile_prototype(&func.prototype)?, func));
+        }
+
+        for (method_name, arg_tokens, body_node, return_type) in methods {
+            let mut func =
+                self.to_func_with_proto(arg_tokens, body_node, Some(method_name), return_type)?;
+            func.prototype.name = Some(format!("{}.{}", class_name, func.prototype.name.unwrap()));
+            func.prototype
+                .args
+                .insert(0, (String::from("soul"), soul_type));
+            funcs.push((self.compile_prototype(&func.prototype)?, func));
+        }
+
+        let init = self.class_new(&record, struct_type, properties, pos)?;
+
+        for (function, func) in funcs {
+            self.compile_fn_body(function, func)?;
+        }
+
+        Ok(init.as_global_value().as_pointer_value().into())
+    }
+
+    /*
+     * Declares the struct of the class, its properties are known once it's defined
+     */
+    pub(crate) fn class_type(&mut self, class_name: &str) -> StructType<'ctx> {
+        let struct_type = self.context.opaque_struct_type(class_name);
+        self.classes.insert(class_name.to_string(), struct_type);
+
+        struct_type
     }
 
     pub(crate) fn class_init(
-        &self,
+        &mut self,
         name: Token,
-        constructor_params: Vec<Node>,
+        constructor_params: Vec<TypedNode>,
         pos: (Position, Position),
     ) -> Result<BasicValueEnum<'ctx>, Error> {
-        Err(self.error(pos, "Node can't be compiled"))
+        let class_name = name.value.into_string();
+        let init = self
+            .get_function(&format!("{}::new", class_name))
+            .ok_or(self.error(pos, "Class not found"))?;
+
+        let soul = self
+            .builder
+            .build_call(init, &[], "tmpnew")
+            .map_err(|_| self.error(pos, "Class not found"))?
+            .try_as_basic_value()
+            .left()
+            .unwrap();
+
+        let mut compiled_args = vec![soul];
+        let constructor = self.get_function(&format!("{}::constructor", class_name));
+        let params = self
+            .constructors
+            .get(&class_name)
+            .cloned()
+            .unwrap_or_default();
+        for (i, param) in constructor_params.into_iter().enumerate() {
+            let (param_pos, param_type) = (param.pos, param.typee.clone());
+            let val = self.compile_node(param)?;
+            compiled_args.push(match params.get(i) {
+                Some(typee) => self.cast(val, &param_type, typee, param_pos)?,
+                None => val,
+            });
+        }
+
+        match constructor {
+            Some(constructor) => {
+                if constructor.count_params() as usize != compiled_args.len() {
+                    return Err(self.error(pos, "Wrong number of arguments to the constructor"));
+                }
+
+                self.builder
+                    .build_call(constructor, &compiled_args[..], "tmpconstructor")
+                    .map_err(|_| self.error(pos, "Invalid constructor"))?;
+            }
+            None => {
+                if compiled_args.len() != 1 {
+                    return Err(self.error(pos, "Class doesn't have a constructor"));
+                }
+            }
+        }
+
+        Ok(soul)
+    }
+
+    /*
+     * Method called on a value of the type, a property of the same name hides it
+     */
+    pub(crate) fn class_method(&self, typee: &Type, name: &str) -> Option<FunctionValue<'ctx>> {
+        match typee {
+            Type::Custom(class) if self.records.get(*class)?.field(name).is_none() => {
+                self.get_function(&format!("{}.{}", class, name))
+            }
+            _ => None,
+        }
+    }
+
+    /*
+     * Builds `<class>::new` which allocates the object and initializes its properties
+     */
+    fn class_new(
+        &mut self,
+        record: &Record,
+        struct_type: StructType<'ctx>,
+        properties: Vec<(Token, TypedNode)>,
+        pos: (Position, Position),
+    ) -> Result<FunctionValue<'ctx>, Error> {
+        let function = self.compile_prototype(&Prototype {
+            name: Some(format!("{}::new", record.name)),
+            args: vec![],
+            ret_type: struct_type.ptr_type(AddressSpace::Generic).into(),
+        })?;
+
+        let parent = self.fn_value_opt;
+        let parental_block = self.builder.get_insert_block();
+
+        let entry = self.context.append_basic_block(function, "entry");
+        self.builder.position_at_end(entry);
+        self.fn_value_opt = Some(function);
+
+        let types = self.field_types(record);
+        struct_type.set_body(&types[..], false);
+
+        let mut values = Vec::with_capacity(properties.len());
+        for ((_, value), (_, typee)) in properties.into_iter().zip(&record.fields) {
+            let (value_pos, value_type) = (value.pos, value.typee.clone());
+            let val = self.compile_node(value)?;
+            values.push(self.cast(val, &value_type, typee, value_pos)?);
+        }
+
+        let soul = self
+            .builder
+            .build_malloc(struct_type, "soul")
+            .map_err(|e| self.error(pos, e))?;
+
+        for (i, val) in values.iter().enumerate() {
+            let ptr = self
+                .builder
+                .build_struct_gep(soul, i as u32, "struct_gep")
+                .ok()
+                .unwrap();
+            self.builder.build_store(ptr, *val);
+        }
+
+        self.builder.build_return(Some(&soul));
+
+        if let Some(block) = parental_block {
+            self.builder.position_at_end(block);
+        }
+        self.fn_value_opt = parent;
+
+        if function.verify(true) {
+            self.fpm.run_on(&function);
+            Ok(function)
+        } else {
+            unsafe {
+                function.delete();
+            }
+
+            Err(self.error(pos, "Invalid generated function"))
+        }
     }
 }
@@ -0,0 +1,232 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+use bzxc_llvm_wrapper::{
+    types::{AnyTypeEnum, BasicTypeEnum, FunctionType, PointerType, StructType},
+    values::{BasicValueEnum, FunctionValue, PointerValue},
+    AddressSpace,
+};
+use bzxc_shared::{any_fn_type, Error, Position, Type};
+
+use crate::Compiler;
+
+impl<'a, 'ctx> Compiler<'a, 'ctx> {
+    /*
+     * Type of the environment every closure takes as its first argument
+     */
+    pub(crate) fn env_type(&self) -> PointerType<'ctx> {
+        self.context.i8_type().ptr_type(AddressSpace::Generic)
+    }
+
+    /*
+     * Type of function values, the function and the environment it's called with
+     */
+    pub(crate) fn closure_type(&self, fn_type: FunctionType<'ctx>) -> StructType<'ctx> {
+        self.context.struct_type(
+            &[
+                fn_type.ptr_type(AddressSpace::Generic).into(),
+                self.env_type().into(),
+            ],
+            false,
+        )
+    }
+
+    pub(crate) fn closure_value(
+        &self,
+        function: PointerValue<'ctx>,
+        env: PointerValue<'ctx>,
+    ) -> BasicValueEnum<'ctx> {
+        let fn_type = match function.get_type().get_element_type() {
+            AnyTypeEnum::FunctionType(x) => x,
+            _ => unreachable!(),
+        };
+
+        let closure = self.closure_type(fn_type).get_undef();
+        let closure = self
+            .builder
+            .build_insert_value(closure, function, 0, "closure_fn")
+            .unwrap();
+        self.builder
+            .build_insert_value(closure, env, 1, "closure_env")
+            .unwrap()
+            .into_struct_value()
+            .into()
+    }
+
+    /*
+     * Wraps a function that doesn't take a environment into a closure returning
+     * the declared type. Methods are bound to the object, which becomes the
+     * environment
+     */
+    pub(crate) fn closure(
+        &self,
+        function: FunctionValue<'ctx>,
+        soul: Option<PointerValue<'ctx>>,
+        return_type: &Type,
+    ) -> BasicValueEnum<'ctx> {
+        let name = format!(
+            "{}::{}",
+            function.get_name().to_str().unwrap(),
+            if soul.is_some() { "bound" } else { "closure" }
+        );
+        let wrapper = self
+            .get_function(&name)
+            .unwrap_or_else(|| self.closure_wrapper(function, &name, soul.is_some(), return_type));
+
+        let env = match soul {
+            Some(soul) => self
+                .builder
+                .build_pointer_cast(soul, self.env_type(), "env"),
+            None => self.env_type().const_null(),
+        };
+        self.closure_value(wrapper.as_global_value().as_pointer_value(), env)
+    }
+
+    fn closure_wrapper(
+        &self,
+        function: FunctionValue<'ctx>,
+        name: &str,
+        bound: bool,
+        return_type: &Type,
+    ) -> FunctionValue<'ctx> {
+        /* Only the declared parameters of a variadic extern can be passed through it */
+        let fn_type = function.get_type();
+        let mut params = fn_type.get_param_types();
+        if bound {
+            params.remove(0);
+        }
+        params.insert(0, self.env_type().into());
+
+        let ret_type = self.llvm_type(return_type);
+        let wrapper =
+            self.module
+                .add_function(name, any_fn_type(ret_type, &params[..], false), None);
+
+        let parental_block = self.builder.get_insert_block();
+        let entry = self.context.append_basic_block(wrapper, "entry");
+        self.builder.position_at_end(entry);
+
+        let mut args = wrapper.get_params();
+        let env = args.remove(0);
+        if bound {
+            let soul = self.builder.build_pointer_cast(
+                env.into_pointer_value(),
+                fn_type.get_param_types()[0].into_pointer_type(),
+                "soul",
+            );
+            args.insert(0, soul.into());
+        }
+
+        let call = self
+            .builder
+            .build_call(function, &args[..], "tmpcall")
+            .unwrap();
+        /* Externs the driver declares, like 'printf', can return a narrower 'int' */
+        match (call.try_as_basic_value().left(), ret_type) {
+            (Some(BasicValueEnum::IntValue(int)), AnyTypeEnum::IntType(expected)) => {
+                let value = self.builder.build_int_cast(int, expected, "ret_cast");
+                self.builder.build_return(Some(&value))
+            }
+            (Some(value), _) => self.builder.build_return(Some(&value)),
+            (None, _) => self.builder.build_return(None),
+        };
+
+        if let Some(block) = parental_block {
+            self.builder.position_at_end(block);
+        }
+
+        wrapper
+    }
+
+    /*
+     * Copies the captured variables into a environment on the heap
+     */
+    pub(crate) fn environment(
+        &self,
+        captures: Vec<String>,
+        pos: (Position, Position),
+    ) -> Result<(Vec<(String, BasicTypeEnum<'ctx>)>, PointerValue<'ctx>), Error> {
+        let mut variables = Vec::with_capacity(captures.len());
+        let mut values = Vec::with_capacity(captures.len());
+        for name in captures {
+            let (ptr, _) = self
+                .lookup(&name)
+                .ok_or_else(|| self.error(pos, "Variable not found to be captured"))?;
+            let value = self.builder.build_load(ptr, &name);
+            variables.push((name, value.get_type()));
+            values.push(value);
+        }
+
+        let env_type = self.context.struct_type(
+            &variables
+                .iter()
+                .map(|x| x.1)
+                .collect::<Vec<BasicTypeEnum>>()[..],
+            false,
+        );
+        let env = self
+            .builder
+            .build_malloc(env_type, "env")
+            .map_err(|e| self.error(pos, e))?;
+        for (i, value) in values.into_iter().enumerate() {
+            let ptr = self
+                .builder
+                .build_struct_gep(env, i as u32, "env_gep")
+                .unwrap();
+            self.builder.build_store(ptr, value);
+        }
+
+        Ok((
+            variables,
+            self.builder.build_pointer_cast(env, self.env_type(), "env"),
+        ))
+    }
+
+    /*
+     * Declares the captured variables of the closure being compiled, and its
+     * own name so it can call itself
+     */
+    pub(crate) fn unpack_environment(
+        &mut self,
+        function: FunctionValue<'ctx>,
+        name: Option<String>,
+        captures: &[(String, BasicTypeEnum<'ctx>)],
+    ) {
+        let env = function.get_first_param().unwrap().into_pointer_value();
+        let env_type = self.context.struct_type(
+            &captures.iter().map(|x| x.1).collect::<Vec<BasicTypeEnum>>()[..],
+            false,
+        );
+        let ptr =
+            self.builder
+                .build_pointer_cast(env, env_type.ptr_type(AddressSpace::Generic), "env");
+
+        for (i, (name, typee)) in captures.iter().enumerate() {
+            let value_ptr = self
+                .builder
+                .build_struct_gep(ptr, i as u32, "env_gep")
+                .unwrap();
+            let value = self.builder.build_load(value_ptr, name);
+            let alloca = self.create_entry_block_alloca(name, *typee);
+            self.builder.build_store(alloca, value);
+            self.declare(name.clone(), (alloca, false));
+        }
+
+        if let Some(name) = name {
+            let closure = self.closure_value(function.as_global_value().as_pointer_value(), env);
+            let alloca = self.create_entry_block_alloca(&name, closure.get_type());
+            self.builder.build_store(alloca, closure);
+            self.declare(name, (alloca, false));
+        }
+    }
+}
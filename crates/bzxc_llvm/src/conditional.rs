@@ -11,16 +11,21 @@
  * limitations under the License.
 */
 
-use bzxc_shared::{Error, Node};
-use bzxc_llvm_wrapper::values::BasicValueEnum;
+use bzxc_llvm_wrapper::{
+    basic_block::BasicBlock,
+    values::{BasicValue, BasicValueEnum},
+};
+use bzxc_shared::{Error, Position, Type, TypedNode};
 
 use crate::Compiler;
 
 impl<'a, 'ctx> Compiler<'a, 'ctx> {
     pub(crate) fn if_decl(
         &mut self,
-        cases: Vec<(Node, Node)>,
-        else_case: Option<Node>,
+        cases: Vec<(TypedNode, TypedNode)>,
+        else_case: Option<TypedNode>,
+        typee: Type,
+        pos: (Position, Position),
     ) -> Result<BasicValueEnum<'ctx>, Error> {
         let mut blocks = vec![self.builder.get_insert_block().unwrap()];
         let parent = self.fn_value();
@@ -39,13 +44,16 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
         let after_block = self.context.append_basic_block(parent, "after");
         blocks.push(after_block);
 
-        for (i, (cond, body)) in cases.iter().enumerate() {
+        let mut incoming = vec![];
+        for (i, (cond, body)) in cases.into_iter().enumerate() {
             let then_block = blocks[i];
             let else_block = blocks[i + 1];
 
             self.builder.position_at_end(then_block);
 
-            let condition = self.compile_node(cond.clone())?;
+            let (cond_pos, cond_type) = (cond.pos, cond.typee.clone());
+            let condition = self.compile_node(cond)?;
+            let condition = self.cast(condition, &cond_type, &Type::Boolean, cond_pos)?;
             let conditional_block = self.context.prepend_basic_block(else_block, "if_body");
 
             self.builder.build_conditional_branch(
@@ -55,18 +63,53 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
             );
 
             self.builder.position_at_end(conditional_block);
-            self.compile_node(body.clone())?;
-            self.builder.build_unconditional_branch(after_block);
+            self.if_branch(body, &typee, after_block, &mut incoming)?;
         }
 
         if let Some(else_block) = else_block {
             self.builder.position_at_end(else_block);
-            self.compile_node(else_case.unwrap())?;
-            self.builder.build_unconditional_branch(after_block);
+            self.if_branch(else_case.unwrap(), &typee, after_block, &mut incoming)?;
         }
 
         self.builder.position_at_end(after_block);
 
-        Ok(self.context.i128_type().const_int(0, false).into())
+        if typee == Type::Void || incoming.is_empty() {
+            return Ok(self.context.i128_type().const_int(0, false).into());
+        }
+
+        let phi = self.builder.build_phi(incoming[0].0.get_type(), "if_value");
+        for (value, block) in &incoming {
+            if value.get_type() != incoming[0].0.get_type() {
+                return Err(self.error(pos, "Branches of the 'if' have different types"));
+            }
+            phi.add_incoming(&[(value as &dyn BasicValue, *block)]);
+        }
+
+        Ok(phi.as_basic_value())
+    }
+
+    /*
+     * Compiles a branch, collecting its value for the phi if it falls through to `after_block`
+     */
+    fn if_branch(
+        &mut self,
+        body: TypedNode,
+        typee: &Type,
+        after_block: BasicBlock<'ctx>,
+        incoming: &mut Vec<(BasicValueEnum<'ctx>, BasicBlock<'ctx>)>,
+    ) -> Result<(), Error> {
+        let (body_pos, body_type) = (body.pos, body.typee.clone());
+        let value = self.compile_block(body)?;
+        if self.is_terminated() {
+            return Ok(());
+        }
+
+        if *typee != Type::Void {
+            let value = self.cast(value, &body_type, typee, body_pos)?;
+            incoming.push((value, self.builder.get_insert_block().unwrap()));
+        }
+        self.builder.build_unconditional_branch(after_block);
+
+        Ok(())
     }
 }
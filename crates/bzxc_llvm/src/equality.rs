@@ -0,0 +1,186 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+use bzxc_llvm_wrapper::{
+    values::{BasicValueEnum, IntValue},
+    AddressSpace, FloatPredicate, IntPredicate,
+};
+use bzxc_shared::{Error, Position, Type};
+
+use crate::Compiler;
+
+impl<'a, 'ctx> Compiler<'a, 'ctx> {
+    /*
+     * Compares two values of the type at runtime, strings by content and
+     * arrays and objects element-wise. Class instances and closures compare by identity.
+     * NaN isn't equal to anything, so '!=' is true for it
+     */
+    pub(crate) fn equality(
+        &self,
+        lhs: BasicValueEnum<'ctx>,
+        rhs: BasicValueEnum<'ctx>,
+        typee: &Type,
+        pos: (Position, Position),
+    ) -> Result<IntValue<'ctx>, Error> {
+        match typee {
+            Type::Int | Type::Boolean | Type::Char => Ok(self.builder.build_int_compare(
+                IntPredicate::EQ,
+                lhs.into_int_value(),
+                rhs.into_int_value(),
+                "tmpeq",
+            )),
+            Type::Float => Ok(self.builder.build_float_compare(
+                FloatPredicate::OEQ,
+                lhs.into_float_value(),
+                rhs.into_float_value(),
+                "tmpeq",
+            )),
+            Type::String => {
+                let i8_ptr = self.context.i8_type().ptr_type(AddressSpace::Generic);
+                let strcmp = self.runtime_fn(
+                    "strcmp",
+                    self.context
+                        .i32_type()
+                        .fn_type(&[i8_ptr.into(), i8_ptr.into()], false),
+                );
+                let cmp = self
+                    .builder
+                    .build_call(strcmp, &[lhs, rhs], "tmpstrcmp")
+                    .map_err(|_| self.error(pos, "Invalid string comparison"))?
+                    .try_as_basic_value()
+                    .left()
+                    .unwrap()
+                    .into_int_value();
+
+                Ok(self.builder.build_int_compare(
+                    IntPredicate::EQ,
+                    cmp,
+                    self.context.i32_type().const_zero(),
+                    "tmpeq",
+                ))
+            }
+            Type::Array(element) => {
+                let (lhs, rhs) = (lhs.into_pointer_value(), rhs.into_pointer_value());
+                let elem = self.elem_type(element, pos)?;
+                let parent = self.fn_value();
+                let i64_type = self.context.i64_type();
+
+                let len = self.array_len(lhs);
+                let same_len = self.builder.build_int_compare(
+                    IntPredicate::EQ,
+                    len,
+                    self.array_len(rhs),
+                    "same_len",
+                );
+                let result_alloca =
+                    self.create_entry_block_alloca("arr_eq", self.context.bool_type());
+                self.builder.build_store(result_alloca, same_len);
+                let index_alloca = self.create_entry_block_alloca("index", i64_type);
+                self.builder
+                    .build_store(index_alloca, i64_type.const_zero());
+
+                let cond_block = self.context.append_basic_block(parent, "cmp_cond");
+                let body_block = self.context.append_basic_block(parent, "cmp_body");
+                let done_block = self.context.append_basic_block(parent, "cmp_done");
+                self.builder.build_unconditional_branch(cond_block);
+
+                /* Keep going while everything matched so far */
+                self.builder.position_at_end(cond_block);
+                let index = self
+                    .builder
+                    .build_load(index_alloca, "index")
+                    .into_int_value();
+                let result = self
+                    .builder
+                    .build_load(result_alloca, "arr_eq")
+                    .into_int_value();
+                let in_bounds =
+                    self.builder
+                        .build_int_compare(IntPredicate::SLT, index, len, "in_bounds");
+                let more = self.builder.build_and(result, in_bounds, "more");
+                self.builder
+                    .build_conditional_branch(more, body_block, done_block);
+
+                self.builder.position_at_end(body_block);
+                let left = self.element(lhs, elem, index);
+                let right = self.element(rhs, elem, index);
+                let left = self.builder.build_load(left, "lhs_elem");
+                let right = self.builder.build_load(right, "rhs_elem");
+                let eq = self.equality(left, right, element, pos)?;
+                self.builder.build_store(result_alloca, eq);
+                let next = self
+                    .builder
+                    .build_int_add(index, i64_type.const_int(1, false), "next");
+                self.builder.build_store(index_alloca, next);
+                self.builder.build_unconditional_branch(cond_block);
+
+                self.builder.position_at_end(done_block);
+                Ok(self
+                    .builder
+                    .build_load(result_alloca, "arr_eq")
+                    .into_int_value())
+            }
+            Type::Object(properties) => {
+                let mut result = self.context.bool_type().const_int(1, false);
+                for (name, field) in properties {
+                    let left = self.prop_ptr(lhs, typee, name, pos)?;
+                    let right = self.prop_ptr(rhs, typee, name, pos)?;
+                    let left = self.builder.build_load(left, "lhs_prop");
+                    let right = self.builder.build_load(right, "rhs_prop");
+                    let eq = self.equality(left, right, field, pos)?;
+                    result = self.builder.build_and(result, eq, "tmpand");
+                }
+
+                Ok(result)
+            }
+            Type::Function(_, _) => {
+                let (lhs, rhs) = (lhs.into_struct_value(), rhs.into_struct_value());
+                let int = self.context.i64_type();
+                let mut result = self.context.bool_type().const_int(1, false);
+                for i in 0..2 {
+                    let left = self
+                        .builder
+                        .build_extract_value(lhs, i, "lhs_closure")
+                        .unwrap();
+                    let right = self
+                        .builder
+                        .build_extract_value(rhs, i, "rhs_closure")
+                        .unwrap();
+                    let eq = self.builder.build_int_compare(
+                        IntPredicate::EQ,
+                        self.builder
+                            .build_ptr_to_int(left.into_pointer_value(), int, "lhs_addr"),
+                        self.builder
+                            .build_ptr_to_int(right.into_pointer_value(), int, "rhs_addr"),
+                        "tmpeq",
+                    );
+                    result = self.builder.build_and(result, eq, "tmpand");
+                }
+
+                Ok(result)
+            }
+            Type::Custom(_) => {
+                let int = self.context.i64_type();
+                Ok(self.builder.build_int_compare(
+                    IntPredicate::EQ,
+                    self.builder
+                        .build_ptr_to_int(lhs.into_pointer_value(), int, "lhs_addr"),
+                    self.builder
+                        .build_ptr_to_int(rhs.into_pointer_value(), int, "rhs_addr"),
+                    "tmpeq",
+                ))
+            }
+            _ => Err(self.error(pos, "Can't compare values of this type")),
+        }
+    }
+}
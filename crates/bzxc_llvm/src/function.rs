@@ -11,12 +11,16 @@
  * limitations under the License.
 */
 
+use std::collections::HashMap;
+
 use bzxc_llvm_wrapper::{
     module::Linkage,
     types::{AnyTypeEnum, BasicTypeEnum},
     values::{BasicValue, BasicValueEnum, FunctionValue},
 };
-use bzxc_shared::{any_fn_type, try_any_to_basic, Error, Node, Position, Token, Type};
+use bzxc_shared::{
+    any_fn_type, try_any_to_basic, Error, Position, Token, Type, TypedKind, TypedNode,
+};
 use rand::{distributions::Alphanumeric, Rng};
 
 use crate::{Compiler, Function, Prototype};
@@ -24,7 +28,7 @@ use crate::{Compiler, Function, Prototype};
 impl<'a, 'ctx> Compiler<'a, 'ctx> {
     pub(crate) fn compile_prototype(
         &self,
-        proto: &'a Prototype<'ctx>,
+        proto: &Prototype<'ctx>,
     ) -> Result<FunctionValue<'ctx>, Error> {
         let ret_type = proto.ret_type;
         let args_types = proto
@@ -62,10 +66,17 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
         &mut self,
         func: Function<'ctx>,
     ) -> Result<FunctionValue<'ctx>, Error> {
-        let parent = self.fn_value_opt.clone();
+        let function = self.compile_prototype(&func.prototype)?;
+        self.compile_fn_body(function, func)
+    }
 
+    pub(crate) fn compile_fn_body(
+        &mut self,
+        function: FunctionValue<'ctx>,
+        func: Function<'ctx>,
+    ) -> Result<FunctionValue<'ctx>, Error> {
+        let parent = self.fn_value_opt.clone();
         let proto = &func.prototype;
-        let function = self.compile_prototype(&proto)?;
 
         let parental_block = self.builder.get_insert_block();
 
@@ -73,8 +84,12 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
         self.builder.position_at_end(entry);
 
         self.fn_value_opt = Some(function);
+        let outer_return = std::mem::replace(&mut self.return_type, func.return_type.clone());
 
-        self.variables.reserve(proto.args.len());
+        /* A function only sees its own locals */
+        let outer = std::mem::replace(&mut self.variables, vec![HashMap::new()]);
+        let outer_loops = std::mem::take(&mut self.loops);
+        self.generics.push(HashMap::new());
 
         for (i, arg) in function.get_param_iter().enumerate() {
             let arg_name = proto.args[i].0.as_str();
@@ -82,16 +97,37 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
 
             self.builder.build_store(alloca, arg);
 
-            self.variables
-                .insert(proto.args[i].0.clone(), (alloca, false));
+            self.declare(proto.args[i].0.clone(), (alloca, false));
         }
 
-        let body = self.compile_node(func.body.clone())?;
+        if !func.captures.is_empty() {
+            self.unpack_environment(function, proto.name.clone(), &func.captures);
+        }
 
-        if let AnyTypeEnum::VoidType(_) = func.prototype.ret_type {
-            self.builder.build_return(None);
-        } else {
-            self.builder.build_return(Some(&body));
+        let body = self.compile_node(func.body.clone())?;
+        let block = self.builder.get_insert_block().unwrap();
+
+        if block.get_terminator().is_none() {
+            if block.get_first_use().is_none() && function.get_first_basic_block() != Some(block) {
+                self.builder.build_unreachable();
+            } else {
+                match function.get_type().get_return_type() {
+                    Some(ret_type) => {
+                        let body =
+                            self.cast(body, &func.body.typee, &func.return_type, func.body.pos)?;
+                        if body.get_type() != ret_type {
+                            return Err(self.error(
+                                func.body.pos,
+                                "Expected a 'return' with the declared return type",
+                            ));
+                        }
+                        self.builder.build_return(Some(&body));
+                    }
+                    None => {
+                        self.builder.build_return(None);
+                    }
+                }
+            }
         }
 
         if parental_block.is_some() {
@@ -99,6 +135,10 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
         }
 
         self.fn_value_opt = parent;
+        self.return_type = outer_return;
+        self.variables = outer;
+        self.loops = outer_loops;
+        self.generics.pop();
 
         if function.verify(true) {
             self.fpm.run_on(&function);
@@ -113,50 +153,183 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
                 function.delete();
             }
 
-            Err(self.error(func.body.get_pos(), "Invalid generated function"))
+            Err(self.error(func.body.pos, "Invalid generated function"))
         }
     }
 
     pub(crate) fn fun_decl(
         &mut self,
         arg_tokens: Vec<(Token, Type)>,
-        body_node: Node,
+        body_node: TypedNode,
         name: Option<Token>,
         return_type: Type,
+        captures: Vec<String>,
+        pos: (Position, Position),
     ) -> Result<BasicValueEnum<'ctx>, Error> {
-        let func = self.to_func_with_proto(arg_tokens, body_node, name, return_type)?;
+        let typee = Type::Function(
+            arg_tokens.iter().map(|x| x.1.clone()).collect(),
+            Box::new(return_type.clone()),
+        );
+        if captures.is_empty() && !typee.vars().is_empty() {
+            /* Top level ones were registered when hoisting */
+            if let (Some(name), false) = (&name, self.is_top_level()) {
+                let function = TypedNode::new(
+                    TypedKind::FunDef {
+                        name: Some(name.clone()),
+                        body_node: Box::new(body_node),
+                        arg_tokens,
+                        return_type,
+                        captures,
+                    },
+                    typee,
+                    pos,
+                );
+                self.generics
+                    .last_mut()
+                    .unwrap()
+                    .insert(name.value.into_string(), function);
+            }
+            return Ok(self.context.i128_type().const_int(0, false).into());
+        }
+
+        let mut func = self.to_func_with_proto(arg_tokens, body_node, name.clone(), return_type)?;
+        if captures.is_empty() {
+            let hoisted = match &name {
+                Some(name) if self.is_top_level() => self
+                    .get_function(&name.value.into_string())
+                    .filter(|x| x.count_basic_blocks() == 0),
+                _ => None,
+            };
+            let return_type = func.return_type.clone();
+            let fun = match hoisted {
+                Some(function) => self.compile_fn_body(function, func)?,
+                None => self.compile_fn(func)?,
+            };
+            return Ok(self.closure(fun, None, &return_type));
+        }
+
+        let (captures, env) = self.environment(captures, pos)?;
+        func.prototype
+            .args
+            .insert(0, (String::from("closure.env"), self.env_type().into()));
+        func.captures = captures;
+
         let fun = self.compile_fn(func)?;
+        let closure = self.closure_value(fun.as_global_value().as_pointer_value(), env);
+
+        /* Named closures are called through a local, as they need their environment */
+        if let Some(name) = name {
+            let name = name.value.into_string();
+            let alloca = self.create_entry_block_alloca(&name, closure.get_type());
+            self.builder.build_store(alloca, closure);
+            self.declare(name, (alloca, false));
+        }
 
-        Ok(fun.as_global_value().as_pointer_value().into())
+        Ok(closure)
     }
 
     pub(crate) fn fun_call(
         &mut self,
-        node_to_call: Node,
-        args: Vec<Node>,
+        node_to_call: TypedNode,
+        args: Vec<TypedNode>,
         pos: (Position, Position),
     ) -> Result<BasicValueEnum<'ctx>, Error> {
-        let mut compiled_args = Vec::with_capacity(args.len());
+        if let TypedKind::ObjectPropAccess { object, property } = &node_to_call.node {
+            if let Type::Array(_) = object.typee {
+                return self.array_method(*object.clone(), property.clone(), args, pos);
+            }
+        }
 
-        for arg in args {
-            compiled_args.push(self.compile_node(arg)?);
+        let (params, ret_type) = match &node_to_call.typee {
+            Type::Function(params, ret) => (params.clone(), self.llvm_type(ret)),
+            _ => return Err(self.error(pos, "Expected a function found something else")),
+        };
+        let mut compiled_args = Vec::with_capacity(args.len() + 1);
+
+        if let TypedKind::ObjectPropAccess { object, property } = &node_to_call.node {
+            if let Some(method) = self.class_method(&object.typee, &property.value.into_string()) {
+                if method.count_params() as usize != args.len() + 1 {
+                    return Err(self.error(pos, "Wrong number of arguments"));
+                }
+
+                compiled_args.push(self.compile_node(*object.clone())?);
+                for (arg, typee) in args.into_iter().zip(params.iter()) {
+                    let (arg_pos, arg_type) = (arg.pos, arg.typee.clone());
+                    let val = self.compile_node(arg)?;
+                    compiled_args.push(self.cast(val, &arg_type, typee, arg_pos)?);
+                }
+
+                let call = self
+                    .builder
+                    .build_call(method, &compiled_args[..], "tmpcall")
+                    .map_err(|_| self.error(pos, "Not a function"))?;
+                return Ok(call
+                    .try_as_basic_value()
+                    .left_or(self.context.i128_type().const_int(0, false).into()));
+            }
         }
 
-        let func = self.compile_node(node_to_call)?;
-        if !func.is_pointer_value() {
-            return Err(self.error(pos, "Expected a Function pointer found something else"));
+        /* Functions and externs called by name don't need a closure */
+        let direct = match &node_to_call.node {
+            TypedKind::VarAccessNode { token } => {
+                let name = token.value.into_string();
+                match (self.lookup(&name), self.generic(&name)) {
+                    (Some(_), _) => None,
+                    (None, Some(function)) => {
+                        Some(self.specialize(&name, function, &node_to_call.typee)?)
+                    }
+                    (None, None) => self.get_function(&name),
+                }
+            }
+            _ => None,
+        };
+
+        let func = match direct {
+            Some(function) => function.as_global_value().as_pointer_value(),
+            None => {
+                let closure = self.compile_node(node_to_call)?.into_struct_value();
+                let env = self
+                    .builder
+                    .build_extract_value(closure, 1, "closure_env")
+                    .unwrap();
+                compiled_args.push(env);
+                self.builder
+                    .build_extract_value(closure, 0, "closure_fn")
+                    .unwrap()
+                    .into_pointer_value()
+            }
+        };
+
+        /* Arguments past the parameters of a variadic extern are passed as they are */
+        for (i, arg) in args.into_iter().enumerate() {
+            let (arg_pos, arg_type) = (arg.pos, arg.typee.clone());
+            let val = self.compile_node(arg)?;
+            compiled_args.push(match params.get(i) {
+                Some(typee) => self.cast(val, &arg_type, typee, arg_pos)?,
+                None => val,
+            });
         }
 
-        let call =
-            self.builder
-                .build_call(func.into_pointer_value(), &compiled_args[..], "tmpcall");
+        let call = self.builder.build_call(func, &compiled_args[..], "tmpcall");
 
-        match call {
-            Ok(call) => Ok(call
+        let value = match call {
+            Ok(call) => call
                 .try_as_basic_value()
-                .left_or(self.context.i128_type().const_int(0, false).into())),
-            Err(_) => Err(self.error(pos, "Not a function")),
-        }
+                .left_or(self.context.i128_type().const_int(0, false).into()),
+            Err(_) => return Err(self.error(pos, "Not a function")),
+        };
+
+        /* Externs the driver declares, like 'printf', can return a narrower 'int' */
+        Ok(match (value, ret_type) {
+            (BasicValueEnum::IntValue(int), AnyTypeEnum::IntType(expected))
+                if int.get_type() != expected =>
+            {
+                self.builder
+                    .build_int_cast(int, expected, "ret_cast")
+                    .into()
+            }
+            (value, _) => value,
+        })
     }
 
     pub(crate) fn fun_extern(
@@ -166,56 +339,184 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
         return_type: Type,
         var_args: bool,
     ) -> Result<BasicValueEnum<'ctx>, Error> {
+        let function = self.extern_fn(&name, &arg_tokens, &return_type, var_args);
+        Ok(self.closure(function, None, &return_type))
+    }
+
+    fn extern_fn(
+        &self,
+        name: &Token,
+        arg_tokens: &[Type],
+        return_type: &Type,
+        var_args: bool,
+    ) -> FunctionValue<'ctx> {
         let args_types = &arg_tokens
             .iter()
-            .map(|x| try_any_to_basic(x.to_llvm_type(&self.context)))
+            .map(|x| try_any_to_basic(self.llvm_type(x)))
             .collect::<Vec<BasicTypeEnum>>()[..];
-        Ok(self
-            .module
-            .add_function(
-                &name.value.into_string(),
-                any_fn_type(return_type.to_llvm_type(self.context), args_types, var_args),
+        let name = name.value.into_string();
+        self.get_function(&name).unwrap_or_else(|| {
+            self.module.add_function(
+                &name,
+                any_fn_type(self.llvm_type(return_type), args_types, var_args),
                 Some(Linkage::External),
             )
-            .as_global_value()
-            .as_pointer_value()
-            .into())
+        })
+    }
+
+    /*
+     * Declares every top level function and extern before compiling the
+     * program, so they can be called before their definition
+     */
+    pub(crate) fn hoist(&mut self, body: &TypedNode) -> Result<(), Error> {
+        let statements = match &body.node {
+            TypedKind::Statements { statements } => statements,
+            _ => return Ok(()),
+        };
+
+        /* Classes first, as the signatures can refer to them */
+        for statement in statements {
+            if let TypedKind::ClassDefNode { name, .. } = &statement.node {
+                self.class_type(&name.value.into_string());
+            }
+        }
+
+        for statement in statements {
+            match &statement.node {
+                TypedKind::FunDef {
+                    name: Some(name),
+                    captures,
+                    ..
+                } if captures.is_empty() && !statement.typee.vars().is_empty() => {
+                    self.generics
+                        .last_mut()
+                        .unwrap()
+                        .insert(name.value.into_string(), statement.clone());
+                }
+                TypedKind::FunDef {
+                    name: Some(name),
+                    arg_tokens,
+                    return_type,
+                    captures,
+                    ..
+                } if captures.is_empty() => {
+                    let prototype = self.prototype(arg_tokens, Some(name.clone()), return_type);
+                    self.compile_prototype(&prototype)?;
+                }
+                TypedKind::ExternNode {
+                    name,
+                    arg_tokens,
+                    return_type,
+                    var_args,
+                } => {
+                    self.extern_fn(name, arg_tokens, return_type, *var_args);
+                }
+                _ => (),
+            }
+        }
+
+        Ok(())
+    }
+
+    /*
+     * Compiles the generic function with its type variables replaced by the
+     * types of the use, once for each set of types it's used at
+     */
+    pub(crate) fn specialize(
+        &mut self,
+        name: &str,
+        mut function: TypedNode,
+        typee: &Type,
+    ) -> Result<FunctionValue<'ctx>, Error> {
+        let mut vars = HashMap::new();
+        function.typee.bind(typee, &mut vars);
+        let symbol = format!(
+            "{}.{}<{}>",
+            name,
+            function.pos.0.index,
+            function.typee.instantiate(&vars)
+        );
+        if let Some(specialized) = self.get_function(&symbol) {
+            return Ok(specialized);
+        }
+
+        function.map_types(&|x| x.instantiate(&vars));
+        function.register_records(&mut self.records);
+        match function.node {
+            TypedKind::FunDef {
+                arg_tokens,
+                body_node,
+                return_type,
+                ..
+            } => {
+                let mut func =
+                    self.to_func_with_proto(arg_tokens, *body_node, None, return_type)?;
+                func.prototype.name = Some(symbol);
+                self.compile_fn(func)
+            }
+            _ => Err(self.error(function.pos, "Expected a function")),
+        }
     }
 
     pub(crate) fn ret(
-        &self,
-        node: Option<Node>,
+        &mut self,
+        node: Option<TypedNode>,
         pos: (Position, Position),
     ) -> Result<BasicValueEnum<'ctx>, Error> {
-        Err(self.error(pos, "Node can't be compiled"))
+        match (node, self.fn_value().get_type().get_return_type()) {
+            (Some(node), Some(ret_type)) => {
+                let typee = node.typee.clone();
+                let val = self.compile_node(node)?;
+                let val = self.cast(val, &typee, &self.return_type.clone(), pos)?;
+                if val.get_type() != ret_type {
+                    return Err(self.error(pos, "Returned value doesn't match the return type"));
+                }
+
+                self.builder.build_return(Some(&val));
+                Ok(val)
+            }
+            (None, None) => {
+                self.builder.build_return(None);
+                Ok(self.context.i128_type().const_int(0, false).into())
+            }
+            (Some(_), None) => Err(self.error(pos, "Can't return a value from a 'void' function")),
+            (None, Some(_)) => Err(self.error(pos, "Expected a value to return")),
+        }
     }
 
     pub(crate) fn to_func_with_proto(
         &self,
         arg_tokens: Vec<(Token, Type)>,
-        body_node: Node,
+        body_node: TypedNode,
         name: Option<Token>,
         return_type: Type,
     ) -> Result<Function<'ctx>, Error> {
         Ok(Function {
-            prototype: Prototype {
-                name: if name.is_none() {
-                    None
-                } else {
-                    Some(name.unwrap().value.into_string())
-                },
-                args: arg_tokens
-                    .iter()
-                    .map(|x| {
-                        (
-                            x.0.value.into_string(),
-                            try_any_to_basic(x.1.to_llvm_type(&self.context)),
-                        )
-                    })
-                    .collect(),
-                ret_type: return_type.to_llvm_type(&self.context),
-            },
+            prototype: self.prototype(&arg_tokens, name, &return_type),
             body: body_node,
+            captures: vec![],
+            return_type,
         })
     }
+
+    fn prototype(
+        &self,
+        arg_tokens: &[(Token, Type)],
+        name: Option<Token>,
+        return_type: &Type,
+    ) -> Prototype<'ctx> {
+        Prototype {
+            name: name.map(|x| x.value.into_string()),
+            args: arg_tokens
+                .iter()
+                .map(|x| {
+                    (
+                        x.0.value.into_string(),
+                        try_any_to_basic(self.llvm_type(&x.1)),
+                    )
+                })
+                .collect(),
+            ret_type: self.llvm_type(return_type),
+        }
+    }
 }
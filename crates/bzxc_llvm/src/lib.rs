@@ -11,27 +11,36 @@
  * limitations under the License.
 */
 #![allow(unused_variables)]
+mod any;
 mod array;
 mod class;
+mod closure;
 mod conditional;
+mod equality;
 mod function;
 mod literals;
 mod loops;
 mod object;
 mod operation;
+mod record;
+mod runtime;
 mod variable;
 
 use std::collections::HashMap;
 
 use bzxc_llvm_wrapper::{
+    basic_block::BasicBlock,
     builder::Builder,
     context::Context,
     module::Module,
     passes::PassManager,
     types::{AnyTypeEnum, BasicType, BasicTypeEnum, StructType},
     values::{BasicValueEnum, FunctionValue, PointerValue},
+    AddressSpace,
+};
+use bzxc_shared::{
+    any_fn_type, try_any_to_basic, Error, Position, Record, Type, TypedKind, TypedNode,
 };
-use bzxc_shared::{Error, Node, Position};
 
 #[derive(Debug, Clone)]
 pub struct Prototype<'ctx> {
@@ -43,7 +52,9 @@ pub struct Prototype<'ctx> {
 #[derive(Debug, Clone)]
 pub struct Function<'ctx> {
     pub prototype: Prototype<'ctx>,
-    pub body: Node,
+    pub body: TypedNode,
+    pub captures: Vec<(String, BasicTypeEnum<'ctx>)>,
+    pub return_type: Type,
 }
 
 pub struct Compiler<'a, 'ctx> {
@@ -52,11 +63,18 @@ pub struct Compiler<'a, 'ctx> {
     pub module: &'a Module<'ctx>,
     pub fpm: &'a PassManager<FunctionValue<'ctx>>,
     pub function: Function<'ctx>,
+    pub bounds_check: bool,
 
-    variables: HashMap<String, (PointerValue<'ctx>, bool)>,
+    variables: Vec<HashMap<String, (PointerValue<'ctx>, bool)>>,
+    generics: Vec<HashMap<String, TypedNode>>,
+    globals: HashMap<String, (PointerValue<'ctx>, bool)>,
+    loops: Vec<(Option<String>, BasicBlock<'ctx>, BasicBlock<'ctx>)>,
     fn_value_opt: Option<FunctionValue<'ctx>>,
-    objects: HashMap<(StructType<'ctx>, String), u32>,
-    object_aligner: u32,
+    return_type: Type,
+    records: HashMap<String, Record>,
+    constructors: HashMap<String, Vec<Type>>,
+    classes: HashMap<String, StructType<'ctx>>,
+    any: StructType<'ctx>,
 }
 
 impl<'a, 'ctx> Compiler<'a, 'ctx> {
@@ -74,6 +92,99 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
         self.fn_value_opt.unwrap()
     }
 
+    #[inline]
+    fn is_terminated(&self) -> bool {
+        self.builder
+            .get_insert_block()
+            .and_then(|block| block.get_terminator())
+            .is_some()
+    }
+
+    fn push_scope(&mut self) {
+        self.variables.push(HashMap::new());
+        self.generics.push(HashMap::new());
+    }
+
+    fn pop_scope(&mut self) {
+        self.variables.pop();
+        self.generics.pop();
+    }
+
+    fn declare(&mut self, name: String, variable: (PointerValue<'ctx>, bool)) {
+        self.variables.last_mut().unwrap().insert(name, variable);
+    }
+
+    fn lookup(&self, name: &str) -> Option<(PointerValue<'ctx>, bool)> {
+        self.variables
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).cloned())
+            .or_else(|| self.globals.get(name).cloned())
+    }
+
+    /*
+     * Finds a generic function, which is compiled for each of the types it's used at
+     */
+    fn generic(&self, name: &str) -> Option<TypedNode> {
+        self.generics
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).cloned())
+    }
+
+    /*
+     * Whether declarations are at the top level of the program, outside of any block
+     */
+    fn is_top_level(&self) -> bool {
+        let main = self
+            .function
+            .prototype
+            .name
+            .as_deref()
+            .and_then(|name| self.get_function(name));
+        self.variables.len() == 1 && self.fn_value_opt.is_some() && self.fn_value_opt == main
+    }
+
+    /*
+     * Compiles the body of a 'if', 'else' or loop in a scope of its own
+     */
+    fn compile_block(&mut self, node: TypedNode) -> Result<BasicValueEnum<'ctx>, Error> {
+        self.push_scope();
+        let result = self.compile_node(node);
+        self.pop_scope();
+
+        result
+    }
+
+    fn llvm_type(&self, typee: &Type) -> AnyTypeEnum<'ctx> {
+        match typee {
+            Type::Any => self.any_type().into(),
+            Type::Custom(name) => match self.classes.get(*name) {
+                Some(class) => class.ptr_type(AddressSpace::Generic).into(),
+                None => typee.to_llvm_type(self.context),
+            },
+            Type::Function(params, ret) => {
+                let mut params = params
+                    .iter()
+                    .map(|x| try_any_to_basic(self.llvm_type(x)))
+                    .collect::<Vec<BasicTypeEnum>>();
+                params.insert(0, self.env_type().into());
+
+                self.closure_type(any_fn_type(self.llvm_type(ret), &params[..], false))
+                    .into()
+            }
+            Type::Array(_) => self.array_type().ptr_type(AddressSpace::Generic).into(),
+            Type::Object(_) => match self.record(typee) {
+                Some(record) => self
+                    .record_type(&record)
+                    .ptr_type(AddressSpace::Generic)
+                    .into(),
+                None => panic!("Objects should be registered by the type checker!"),
+            },
+            _ => typee.to_llvm_type(self.context),
+        }
+    }
+
     fn create_entry_block_alloca<T: BasicType<'ctx>>(
         &self,
         name: &str,
@@ -91,12 +202,16 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
         builder.build_alloca(ty, name)
     }
 
-    fn compile_node(&mut self, node: Node) -> Result<BasicValueEnum<'ctx>, Error> {
-        match node.clone() {
-            Node::Statements { statements } => {
+    fn compile_node(&mut self, node: TypedNode) -> Result<BasicValueEnum<'ctx>, Error> {
+        let pos = node.pos;
+        match node.node {
+            TypedKind::Statements { statements } => {
                 let mut ret = None;
                 for statement in statements {
                     ret = Some(self.compile_node(statement)?);
+                    if self.is_terminated() {
+                        break;
+                    }
                 }
 
                 return Ok(if ret.is_none() {
@@ -105,79 +220,102 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
                     ret.unwrap()
                 });
             }
-            Node::WhileNode {
+            TypedKind::WhileNode {
                 condition_node,
                 body_node,
-            } => self.while_loop(*condition_node, *body_node),
-            Node::VarReassignNode { name, typee, value } => {
-                self.var_reassign(name, *value, typee, node.get_pos())
+                label,
+            } => self.while_loop(*condition_node, *body_node, label),
+            TypedKind::VarReassignNode { name, typee, value } => {
+                self.var_reassign(name, *value, typee, node.typee, pos)
             }
-            Node::VarAssignNode {
+            TypedKind::VarAssignNode {
                 name,
                 value,
                 reassignable,
             } => self.var_assign(name, *value, reassignable),
-            Node::VarAccessNode { token } => self.var_access(token, node.get_pos()),
-            Node::UnaryNode {
+            TypedKind::VarAccessNode { token } => self.var_access(token, node.typee, pos),
+            TypedKind::UnaryNode {
                 node: child,
                 op_token,
-            } => self.unary_op(*child, op_token, node.get_pos()),
-            Node::StringNode { token } => self.string(token),
-            Node::NumberNode { token } => self.num(token),
-            Node::IfNode { cases, else_case } => self.if_decl(cases, *else_case),
-            Node::FunDef {
+            } => self.unary_op(*child, op_token, pos),
+            TypedKind::StringNode { token } => self.string(token),
+            TypedKind::NumberNode { token } => self.num(token),
+            TypedKind::IfNode { cases, else_case } => {
+                self.if_decl(cases, *else_case, node.typee, pos)
+            }
+            TypedKind::FunDef {
                 name,
                 arg_tokens,
                 body_node,
                 return_type,
-            } => self.fun_decl(arg_tokens, *body_node, name, return_type),
-            Node::ForNode {
+                captures,
+            } => self.fun_decl(arg_tokens, *body_node, name, return_type, captures, pos),
+            TypedKind::ForNode {
                 var_name_token,
                 start_value,
                 end_value,
                 body_node,
                 step_value_node,
+                inclusive,
+                label,
             } => self.for_loop(
                 var_name_token,
                 *start_value,
                 *end_value,
                 *body_node,
                 *step_value_node,
-                node.get_pos(),
+                inclusive,
+                label,
             ),
-            Node::CharNode { token } => self.char(token),
-            Node::CallNode { node_to_call, args } => {
-                self.fun_call(*node_to_call, args, node.get_pos())
-            }
-            Node::BooleanNode { token } => self.boolean(token),
-            Node::BinaryNode {
+            TypedKind::ForInNode {
+                var_name_token,
+                iterable_node,
+                body_node,
+                label,
+            } => self.for_in(var_name_token, *iterable_node, *body_node, label, pos),
+            TypedKind::CharNode { token } => self.char(token),
+            TypedKind::CallNode { node_to_call, args } => self.fun_call(*node_to_call, args, pos),
+            TypedKind::BooleanNode { token } => self.boolean(token),
+            TypedKind::BinaryNode {
                 left,
                 right,
                 op_token,
-            } => self.binary_op(*left, op_token, *right, node.get_pos()),
-            Node::ArrayNode { element_nodes } => self.array_decl(element_nodes, node.get_pos()),
-            Node::ArrayAcess { array, index } => self.array_access(*array, *index, node.get_pos()),
-            Node::ReturnNode { value } => self.ret(*value, node.get_pos()),
-            Node::ObjectDefNode { properties } => self.obj_decl(properties),
-            Node::ObjectPropAccess { object, property } => {
-                self.obj_get(*object, property, node.get_pos())
+            } => self.binary_op(*left, op_token, *right, pos),
+            TypedKind::ArrayNode { element_nodes } => {
+                self.array_decl(element_nodes, node.typee, pos)
             }
-            Node::ObjectPropEdit {
+            TypedKind::ArrayAcess { array, index } => {
+                self.array_access(*array, *index, node.typee, pos)
+            }
+            TypedKind::ArrayEdit {
+                array,
+                index,
+                typee,
+                new_val,
+            } => self.array_edit(*array, *index, typee, *new_val, node.typee, pos),
+            TypedKind::ReturnNode { value } => self.ret(*value, pos),
+            TypedKind::BreakNode { label } => self.loop_control(true, label, pos),
+            TypedKind::ContinueNode { label } => self.loop_control(false, label, pos),
+            TypedKind::ObjectDefNode { properties } => self.obj_decl(properties, node.typee, pos),
+            TypedKind::ObjectPropAccess { object, property } => {
+                self.obj_get(*object, property, node.typee, pos)
+            }
+            TypedKind::ObjectPropEdit {
                 object,
                 property,
                 new_val,
-            } => self.obj_edit(*object, property, *new_val, node.get_pos()),
-            Node::ClassDefNode {
+            } => self.obj_edit(*object, property, *new_val, node.typee, pos),
+            TypedKind::ClassDefNode {
                 name,
                 constructor,
                 properties,
                 methods,
-            } => self.class_decl(*constructor, properties, name, methods, node.get_pos()),
-            Node::ClassInitNode {
+            } => self.class_decl(*constructor, properties, name, methods, pos),
+            TypedKind::ClassInitNode {
                 name,
                 constructor_params,
-            } => self.class_init(name, constructor_params, node.get_pos()),
-            Node::ExternNode {
+            } => self.class_init(name, constructor_params, pos),
+            TypedKind::ExternNode {
                 name,
                 arg_tokens,
                 return_type,
@@ -188,7 +326,9 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
 
     pub fn compile_main(&mut self) -> Result<FunctionValue<'ctx>, Error> {
         let func = self.function.clone();
-        self.compile_fn(func)
+        let main = self.compile_prototype(&func.prototype)?;
+        self.hoist(&func.body)?;
+        self.compile_fn_body(main, func)
     }
 
     pub fn init(
@@ -197,17 +337,32 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
         module: &'a Module<'ctx>,
         fpm: &'a PassManager<FunctionValue<'ctx>>,
         function: Function<'ctx>,
+        records: HashMap<String, Record>,
+        bounds_check: bool,
     ) -> Compiler<'a, 'ctx> {
+        let any = context.opaque_struct_type("any");
+        any.set_body(
+            &[context.i8_type().ptr_type(AddressSpace::Generic).into()],
+            false,
+        );
+
         Compiler {
             builder,
             context,
             module,
             fpm,
-            variables: HashMap::new(),
+            variables: vec![HashMap::new()],
+            generics: vec![HashMap::new()],
+            globals: HashMap::new(),
+            loops: vec![],
             function,
+            bounds_check,
             fn_value_opt: None,
-            objects: HashMap::new(),
-            object_aligner: 0,
+            return_type: Type::Void,
+            records,
+            constructors: HashMap::new(),
+            classes: HashMap::new(),
+            any,
         }
     }
 }
@@ -11,56 +11,72 @@
  * limitations under the License.
 */
 
-use bzxc_llvm_wrapper::{values::BasicValueEnum, FloatPredicate, IntPredicate};
-use bzxc_shared::{Error, Node, Position, Token};
+use bzxc_llvm_wrapper::{
+    basic_block::BasicBlock,
+    values::{BasicValueEnum, IntValue},
+    FloatPredicate, IntPredicate,
+};
+use bzxc_shared::{Error, Position, Token, Type, TypedNode};
 
 use crate::Compiler;
 
 impl<'a, 'ctx> Compiler<'a, 'ctx> {
+    /*
+     * Runs while the variable is below the end for a positive step, or above
+     * it for a negative one. The end and step are evaluated once, and a zero
+     * step runs no iterations
+     */
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn for_loop(
         &mut self,
         var_name_token: Token,
-        start_value: Node,
-        end_value: Node,
-        body_node: Node,
-        step_value_node: Node,
-        pos: (Position, Position),
+        start_value: TypedNode,
+        end_value: TypedNode,
+        body_node: TypedNode,
+        step_value_node: TypedNode,
+        inclusive: bool,
+        label: Option<Token>,
     ) -> Result<BasicValueEnum<'ctx>, Error> {
         let parent = self.fn_value();
+        let var_name = var_name_token.value.into_string();
 
+        let start_type = start_value.typee.clone();
+        let is_int = start_type == Type::Int;
         let start = self.compile_node(start_value)?;
-        let start_alloca =
-            self.create_entry_block_alloca(&var_name_token.value.into_string(), start.get_type());
+        let (end_pos, end_type) = (end_value.pos, end_value.typee.clone());
+        let (step_pos, step_type) = (step_value_node.pos, step_value_node.typee.clone());
+        let end = self.compile_node(end_value)?;
+        let end = self.cast(end, &end_type, &start_type, end_pos)?;
+        let step = self.compile_node(step_value_node)?;
+        let step = self.cast(step, &step_type, &start_type, step_pos)?;
 
+        let start_alloca = self.create_entry_block_alloca(&var_name, start.get_type());
         self.builder.build_store(start_alloca, start);
 
+        let condition_block = self.context.append_basic_block(parent, "for_cond");
         let loop_block = self.context.append_basic_block(parent, "for_loop");
+        let step_block = self.context.append_basic_block(parent, "for_step");
+        let after_block = self.context.append_basic_block(parent, "afterloop");
 
-        self.builder.build_unconditional_branch(loop_block);
-        self.builder.position_at_end(loop_block);
-
-        let old_val = self.variables.remove(&var_name_token.value.into_string());
-
-        self.variables
-            .insert(var_name_token.value.into_string(), (start_alloca, true));
-
-        self.compile_node(body_node)?;
-        let step = self.compile_node(step_value_node)?;
-        let end_condition = self.compile_node(end_value)?;
+        self.builder.build_unconditional_branch(condition_block);
+        self.builder.position_at_end(condition_block);
+        let curr_var = self.builder.build_load(start_alloca, &var_name);
+        let condition = self.range_condition(curr_var, end, step, is_int, inclusive);
+        self.builder
+            .build_conditional_branch(condition, loop_block, after_block);
 
-        let curr_var = self
-            .builder
-            .build_load(start_alloca, &var_name_token.value.into_string());
-
-        if !((curr_var.is_int_value() && step.is_int_value() && end_condition.is_int_value())
-            || (curr_var.is_float_value()
-                && step.is_float_value()
-                && end_condition.is_float_value()))
-        {
-            return Err(self.error(pos, "Expected same type in all start, step and end"));
+        self.builder.position_at_end(loop_block);
+        self.push_scope();
+        self.declare(var_name.clone(), (start_alloca, true));
+        self.loop_body(label, step_block, after_block, body_node)?;
+        self.pop_scope();
+        if !self.is_terminated() {
+            self.builder.build_unconditional_branch(step_block);
         }
 
-        let next_var: BasicValueEnum = if curr_var.is_int_value() {
+        self.builder.position_at_end(step_block);
+        let curr_var = self.builder.build_load(start_alloca, &var_name);
+        let next_var: BasicValueEnum = if is_int {
             self.builder
                 .build_int_add(curr_var.into_int_value(), step.into_int_value(), "nextvar")
                 .into()
@@ -73,65 +89,275 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
                 )
                 .into()
         };
-
         self.builder.build_store(start_alloca, next_var);
+        self.builder.build_unconditional_branch(condition_block);
+
+        self.builder.position_at_end(after_block);
+
+        Ok(self.context.i128_type().const_int(0, false).into())
+    }
+
+    fn range_condition(
+        &self,
+        curr_var: BasicValueEnum<'ctx>,
+        end: BasicValueEnum<'ctx>,
+        step: BasicValueEnum<'ctx>,
+        is_int: bool,
+        inclusive: bool,
+    ) -> IntValue<'ctx> {
+        let (up, down, below, above) = if is_int {
+            let (curr_var, end, step) = (
+                curr_var.into_int_value(),
+                end.into_int_value(),
+                step.into_int_value(),
+            );
+            let zero = step.get_type().const_zero();
+            let (below, above) = if inclusive {
+                (IntPredicate::SLE, IntPredicate::SGE)
+            } else {
+                (IntPredicate::SLT, IntPredicate::SGT)
+            };
 
-        let end_condition = if curr_var.is_int_value() {
-            self.builder.build_int_compare(
-                IntPredicate::NE,
-                next_var.into_int_value(),
-                end_condition.into_int_value(),
-                "loopcond",
+            (
+                self.builder
+                    .build_int_compare(IntPredicate::SGT, step, zero, "step_up"),
+                self.builder
+                    .build_int_compare(IntPredicate::SLT, step, zero, "step_down"),
+                self.builder
+                    .build_int_compare(below, curr_var, end, "below"),
+                self.builder
+                    .build_int_compare(above, curr_var, end, "above"),
             )
         } else {
-            self.builder.build_float_compare(
-                FloatPredicate::ONE,
-                next_var.into_float_value(),
-                end_condition.into_float_value(),
-                "loopcond",
+            let (curr_var, end, step) = (
+                curr_var.into_float_value(),
+                end.into_float_value(),
+                step.into_float_value(),
+            );
+            let zero = step.get_type().const_zero();
+            let (below, above) = if inclusive {
+                (FloatPredicate::OLE, FloatPredicate::OGE)
+            } else {
+                (FloatPredicate::OLT, FloatPredicate::OGT)
+            };
+
+            (
+                self.builder
+                    .build_float_compare(FloatPredicate::OGT, step, zero, "step_up"),
+                self.builder
+                    .build_float_compare(FloatPredicate::OLT, step, zero, "step_down"),
+                self.builder
+                    .build_float_compare(below, curr_var, end, "below"),
+                self.builder
+                    .build_float_compare(above, curr_var, end, "above"),
             )
         };
 
+        let up = self.builder.build_and(up, below, "up_cond");
+        let down = self.builder.build_and(down, above, "down_cond");
+        self.builder.build_or(up, down, "loopcond")
+    }
+
+    /*
+     * Walks an array or the characters of a string with a hidden index, or
+     * calls 'has_next' and 'next' on an object until it runs out of items
+     */
+    pub(crate) fn for_in(
+        &mut self,
+        var_name_token: Token,
+        iterable_node: TypedNode,
+        body_node: TypedNode,
+        label: Option<Token>,
+        pos: (Position, Position),
+    ) -> Result<BasicValueEnum<'ctx>, Error> {
+        let parent = self.fn_value();
+        let var_name = var_name_token.value.into_string();
+
+        let typee = iterable_node.typee.clone();
+        let iterable = self.compile_node(iterable_node)?;
+
+        let i64_type = self.context.i64_type();
+        let index_alloca = self.create_entry_block_alloca("index", i64_type);
+        self.builder
+            .build_store(index_alloca, i64_type.const_zero());
+
+        let condition_block = self.context.append_basic_block(parent, "for_cond");
+        let loop_block = self.context.append_basic_block(parent, "for_loop");
+        let step_block = self.context.append_basic_block(parent, "for_step");
         let after_block = self.context.append_basic_block(parent, "afterloop");
 
+        self.builder.build_unconditional_branch(condition_block);
+        self.builder.position_at_end(condition_block);
+        let index = self
+            .builder
+            .build_load(index_alloca, "index")
+            .into_int_value();
+        let condition = match &typee {
+            Type::Array(_) => self.builder.build_int_compare(
+                IntPredicate::SLT,
+                index,
+                self.array_len(iterable.into_pointer_value()),
+                "has_next",
+            ),
+            Type::String => {
+                let char_ptr = unsafe {
+                    self.builder
+                        .build_gep(iterable.into_pointer_value(), &[index], "char_ptr")
+                };
+                let char = self.builder.build_load(char_ptr, "char").into_int_value();
+                self.builder.build_int_compare(
+                    IntPredicate::NE,
+                    char,
+                    char.get_type().const_zero(),
+                    "has_next",
+                )
+            }
+            _ => self
+                .iterator_call(iterable, &typee, "has_next", pos)?
+                .into_int_value(),
+        };
         self.builder
-            .build_conditional_branch(end_condition, loop_block, after_block);
-        self.builder.position_at_end(after_block);
-        self.variables.remove(&var_name_token.value.into_string());
+            .build_conditional_branch(condition, loop_block, after_block);
 
-        if let Some(val) = old_val {
-            self.variables
-                .insert(var_name_token.value.into_string(), val);
+        self.builder.position_at_end(loop_block);
+        let element = match &typee {
+            Type::Array(elem) => {
+                let elem = self.elem_type(elem, pos)?;
+                let elem_ptr = self.element(iterable.into_pointer_value(), elem, index);
+                self.builder.build_load(elem_ptr, "elem")
+            }
+            Type::String => {
+                let char_ptr = unsafe {
+                    self.builder
+                        .build_gep(iterable.into_pointer_value(), &[index], "char_ptr")
+                };
+                self.builder.build_load(char_ptr, "char")
+            }
+            _ => self.iterator_call(iterable, &typee, "next", pos)?,
+        };
+        let var_alloca = self.create_entry_block_alloca(&var_name, element.get_type());
+        self.builder.build_store(var_alloca, element);
+
+        self.push_scope();
+        self.declare(var_name, (var_alloca, true));
+        self.loop_body(label, step_block, after_block, body_node)?;
+        self.pop_scope();
+        if !self.is_terminated() {
+            self.builder.build_unconditional_branch(step_block);
         }
 
+        self.builder.position_at_end(step_block);
+        let next_index =
+            self.builder
+                .build_int_add(index, i64_type.const_int(1, false), "next_index");
+        self.builder.build_store(index_alloca, next_index);
+        self.builder.build_unconditional_branch(condition_block);
+
+        self.builder.position_at_end(after_block);
+
         Ok(self.context.i128_type().const_int(0, false).into())
     }
 
+    fn iterator_call(
+        &self,
+        iterator: BasicValueEnum<'ctx>,
+        typee: &Type,
+        name: &str,
+        pos: (Position, Position),
+    ) -> Result<BasicValueEnum<'ctx>, Error> {
+        let method = self
+            .class_method(typee, name)
+            .ok_or_else(|| self.error(pos, "Value is not iterable"))?;
+
+        self.builder
+            .build_call(method, &[iterator], name)
+            .map_err(|_| self.error(pos, "Not a function"))?
+            .try_as_basic_value()
+            .left()
+            .ok_or_else(|| self.error(pos, "Expected a value"))
+    }
+
     pub(crate) fn while_loop(
         &mut self,
-        condition_node: Node,
-        body_node: Node,
+        condition_node: TypedNode,
+        body_node: TypedNode,
+        label: Option<Token>,
     ) -> Result<BasicValueEnum<'ctx>, Error> {
         let parent = self.fn_value();
+        let condition_block = self.context.append_basic_block(parent, "while_cond");
         let loop_block = self.context.append_basic_block(parent, "while_loop");
-
         let after_block = self.context.append_basic_block(parent, "afterloop");
 
-        self.builder.build_conditional_branch(
-            self.compile_node(condition_node.clone())?.into_int_value(),
-            loop_block,
-            after_block,
-        );
+        self.builder.build_unconditional_branch(condition_block);
+        self.builder.position_at_end(condition_block);
+        let condition = self.while_condition(&condition_node)?;
+        self.builder
+            .build_conditional_branch(condition, loop_block, after_block);
 
         self.builder.position_at_end(loop_block);
-        self.compile_node(body_node)?;
-        self.builder.build_conditional_branch(
-            self.compile_node(condition_node.clone())?.into_int_value(),
-            loop_block,
-            after_block,
-        );
+        self.push_scope();
+        self.loop_body(label, condition_block, after_block, body_node)?;
+        self.pop_scope();
+        if !self.is_terminated() {
+            self.builder.build_unconditional_branch(condition_block);
+        }
         self.builder.position_at_end(after_block);
 
         Ok(self.context.i128_type().const_int(0, false).into())
     }
+
+    /*
+     * Compiles the body of a loop, where 'continue' jumps to `next_block` and
+     * 'break' to `after_block`
+     */
+    fn loop_body(
+        &mut self,
+        label: Option<Token>,
+        next_block: BasicBlock<'ctx>,
+        after_block: BasicBlock<'ctx>,
+        body_node: TypedNode,
+    ) -> Result<BasicValueEnum<'ctx>, Error> {
+        self.loops.push((
+            label.map(|x| x.value.into_string()),
+            next_block,
+            after_block,
+        ));
+        let body = self.compile_node(body_node);
+        self.loops.pop();
+
+        body
+    }
+
+    pub(crate) fn loop_control(
+        &mut self,
+        is_break: bool,
+        label: Option<Token>,
+        pos: (Position, Position),
+    ) -> Result<BasicValueEnum<'ctx>, Error> {
+        let name = label.map(|x| x.value.into_string());
+        let (_, next_block, after_block) = self
+            .loops
+            .iter()
+            .rev()
+            .find(|x| name.is_none() || x.0 == name)
+            .cloned()
+            .ok_or_else(|| self.error(pos, "Loop not found"))?;
+
+        self.builder
+            .build_unconditional_branch(if is_break { after_block } else { next_block });
+
+        Ok(self.context.i128_type().const_int(0, false).into())
+    }
+
+    fn while_condition(&mut self, condition_node: &TypedNode) -> Result<IntValue<'ctx>, Error> {
+        let condition = self.compile_node(condition_node.clone())?;
+        Ok(self
+            .cast(
+                condition,
+                &condition_node.typee,
+                &Type::Boolean,
+                condition_node.pos,
+            )?
+            .into_int_value())
+    }
 }
@@ -11,126 +11,113 @@
  * limitations under the License.
 */
 
-use bzxc_llvm_wrapper::values::BasicValueEnum;
-use bzxc_shared::{Error, Node, Position, Token};
+use bzxc_llvm_wrapper::values::{BasicValueEnum, PointerValue};
+use bzxc_shared::{Error, Position, Token, Type, TypedNode};
 
 use crate::Compiler;
 
 impl<'a, 'ctx> Compiler<'a, 'ctx> {
+    /*
+     * Objects live on the heap, so every copy of one refers to the same object
+     */
     pub(crate) fn obj_decl(
         &mut self,
-        properties: Vec<(Token, Node)>,
+        properties: Vec<(Token, TypedNode)>,
+        typee: Type,
+        pos: (Position, Position),
     ) -> Result<BasicValueEnum<'ctx>, Error> {
-        let arr = self
-            .context
-            .i8_type()
-            .array_type(self.object_aligner)
-            .const_zero();
-
-        self.object_aligner += 1;
-
-        let mut values = vec![arr.into()];
-        let mut types = vec![arr.get_type().into()];
-        let mut names = vec![String::new()];
-
-        for (k, v) in &properties {
-            let val = self.compile_node(v.clone())?;
-            values.push(val);
-            types.push(val.get_type());
-            names.push(k.value.into_string());
+        let record = self
+            .record(&typee)
+            .ok_or(self.error(pos, "Expected 'object'"))?;
+        let struct_type = self.record_type(&record);
+
+        let mut values = Vec::with_capacity(properties.len());
+        for ((_, value), (_, typee)) in properties.into_iter().zip(&record.fields) {
+            let (value_pos, value_type) = (value.pos, value.typee.clone());
+            let val = self.compile_node(value)?;
+            values.push(self.cast(val, &value_type, typee, value_pos)?);
         }
 
-        let mut struct_val = self.context.struct_type(&types[..], false).get_undef();
+        let object = self
+            .builder
+            .build_malloc(struct_type, "object")
+            .map_err(|e| self.error(pos, e))?;
 
         for (i, val) in values.iter().enumerate() {
-            struct_val = self
+            let ptr = self
                 .builder
-                .build_insert_value(struct_val, val.clone(), i as u32, "")
-                .unwrap()
-                .into_struct_value();
-        }
-
-        for (i, name) in names.iter().enumerate() {
-            self.objects
-                .insert((struct_val.get_type(), name.clone()), i as u32);
+                .build_struct_gep(object, i as u32, "struct_gep")
+                .ok()
+                .unwrap();
+            self.builder.build_store(ptr, *val);
         }
 
-        Ok(struct_val.into())
+        Ok(object.into())
     }
 
     pub(crate) fn obj_get(
         &mut self,
-        object: Node,
+        object: TypedNode,
         property: Token,
+        prop_type: Type,
         pos: (Position, Position),
     ) -> Result<BasicValueEnum<'ctx>, Error> {
+        let typee = object.typee.clone();
         let struct_val = self.compile_node(object)?;
-        if !struct_val.is_struct_value() {
-            return Err(self.error(pos, "Expected 'object'"));
-        }
-
-        let prop = self
-            .objects
-            .get(&(
-                struct_val.into_struct_value().get_type(),
-                property.value.into_string(),
-            ))
-            .ok_or(self.error(pos, "Property not found on object"))?
-            .clone();
+        let name = property.value.into_string();
 
-        let val = self
-            .builder
-            .build_extract_value(struct_val.into_struct_value(), prop, "extract_obj")
-            .ok_or(self.error(pos, "Property not found on object"))?;
+        if let (Some(method), Type::Function(_, ret)) =
+            (self.class_method(&typee, &name), &prop_type)
+        {
+            return Ok(self.closure(method, Some(struct_val.into_pointer_value()), ret));
+        }
 
-        Ok(val)
+        let ptr = self.prop_ptr(struct_val, &typee, &name, pos)?;
+        Ok(self.builder.build_load(ptr, "load_prop"))
     }
 
     pub(crate) fn obj_edit(
         &mut self,
-        object: Node,
+        object: TypedNode,
         property: Token,
-        new_val: Node,
+        new_val: TypedNode,
+        prop_type: Type,
         pos: (Position, Position),
     ) -> Result<BasicValueEnum<'ctx>, Error> {
+        let val_type = new_val.typee.clone();
         let val = self.compile_node(new_val)?;
+        let val = self.cast(val, &val_type, &prop_type, pos)?;
 
+        let typee = object.typee.clone();
         let struct_val = self.compile_node(object)?;
+        let ptr = self.prop_ptr(struct_val, &typee, &property.value.into_string(), pos)?;
 
-        if !struct_val.is_struct_value() {
-            return Err(self.error(pos, "Expected 'object'"));
-        }
-
-        let i = self
-            .objects
-            .get(&(
-                struct_val.into_struct_value().get_type(),
-                property.value.into_string(),
-            ))
-            .ok_or(self.error(pos, "Property not found on object"))?
-            .clone();
+        self.builder.build_store(ptr, val);
+        Ok(val)
+    }
 
-        let x = self
-            .builder
-            .build_extract_value(struct_val.into_struct_value(), i, "extract_obj")
+    /*
+     * Pointer to a property of a object or class instance, found through the
+     * record of its type
+     */
+    pub(crate) fn prop_ptr(
+        &self,
+        struct_val: BasicValueEnum<'ctx>,
+        typee: &Type,
+        name: &str,
+        pos: (Position, Position),
+    ) -> Result<PointerValue<'ctx>, Error> {
+        let record = self
+            .record(typee)
+            .ok_or(self.error(pos, "Expected 'object'"))?;
+        let (i, _) = record
+            .field(name)
             .ok_or(self.error(pos, "Property not found on object"))?;
 
-        if x.get_type() != val.get_type() {
-            return Err(self.error(pos, "Expected the type it was initialized with."));
-        };
-
-        let struct_ptr = self
-            .builder
-            .build_alloca(struct_val.get_type(), "struct_alloca");
-        self.builder.build_store(struct_ptr, struct_val);
-
-        let ptr = self
+        Ok(self
             .builder
-            .build_struct_gep(struct_ptr, i, "struct_gep")
+            .build_struct_gep(struct_val.into_pointer_value(), i, "struct_gep")
             .ok()
-            .unwrap();
-        self.builder.build_store(ptr, val);
-
-        Ok(struct_val)
+            .unwrap())
     }
 }
@@ -11,131 +11,142 @@
  * limitations under the License.
 */
 
-use bzxc_shared::{DynType, Error, Node, Position, Token, Tokens};
-use bzxc_llvm_wrapper::{values::BasicValueEnum, FloatPredicate, IntPredicate};
+use bzxc_llvm_wrapper::{
+    values::{BasicValueEnum, FloatValue, FunctionValue, IntValue},
+    FloatPredicate, IntPredicate,
+};
+use bzxc_shared::{DynType, Error, Position, Token, Tokens, Type, TypedNode};
 
 use crate::Compiler;
 
 impl<'a, 'ctx> Compiler<'a, 'ctx> {
     pub(crate) fn binary_op(
         &mut self,
-        left: Node,
+        left: TypedNode,
         op_token: Token,
-        right: Node,
+        right: TypedNode,
         pos: (Position, Position),
     ) -> Result<BasicValueEnum<'ctx>, Error> {
+        if op_token.matches(Tokens::Keyword, DynType::String("and".to_string())) {
+            return self.short_circuit(left, right, true);
+        }
+        if op_token.matches(Tokens::Keyword, DynType::String("or".to_string())) {
+            return self.short_circuit(left, right, false);
+        }
+
+        let (left_pos, right_pos) = (left.pos, right.pos);
+        let typee = match (&left.typee, &right.typee) {
+            (Type::Any, Type::Any) => {
+                return Err(self.error(pos, "Can't operate on two 'any' values"))
+            }
+            (Type::Any, typee) | (typee, _) => typee.clone(),
+        };
+        let (left_type, right_type) = (left.typee.clone(), right.typee.clone());
         let left_val = self.compile_node(left)?;
         let right_val = self.compile_node(right)?;
 
+        /* A 'any' operand gets casted to the type of the other one */
+        let left_val = self.cast(left_val, &left_type, &typee, left_pos)?;
+        let right_val = self.cast(right_val, &right_type, &typee, right_pos)?;
+
         match op_token.typee {
             Tokens::DoubleEquals => {
-                return Ok(self
-                    .context
-                    .bool_type()
-                    .const_int((left_val == right_val) as u64, false)
-                    .into())
+                return Ok(self.equality(left_val, right_val, &typee, pos)?.into())
             }
             Tokens::NotEquals => {
-                return Ok(self
-                    .context
-                    .bool_type()
-                    .const_int((left_val != right_val) as u64, false)
-                    .into())
+                let eq = self.equality(left_val, right_val, &typee, pos)?;
+                return Ok(self.builder.build_not(eq, "tmpne").into());
             }
             _ => (),
         }
 
-        if left_val.is_int_value() && right_val.is_int_value() {
+        self.arithmetic(op_token.typee, left_val, right_val, &typee, pos)
+    }
+
+    /*
+     * Applies a arithmetic or comparison operator on two values of the type
+     */
+    pub(crate) fn arithmetic(
+        &mut self,
+        op: Tokens,
+        left_val: BasicValueEnum<'ctx>,
+        right_val: BasicValueEnum<'ctx>,
+        typee: &Type,
+        pos: (Position, Position),
+    ) -> Result<BasicValueEnum<'ctx>, Error> {
+        if let Type::Int | Type::Boolean | Type::Char = typee {
             let lhs = left_val.into_int_value();
             let rhs = right_val.into_int_value();
 
-            let ret = match op_token.typee {
+            let ret = match op {
                 Tokens::Plus => self.builder.build_int_add(lhs, rhs, "tmpadd"),
                 Tokens::Minus => self.builder.build_int_sub(lhs, rhs, "tmpsub"),
                 Tokens::Multiply => self.builder.build_int_mul(lhs, rhs, "tmpmul"),
-                Tokens::Divide => self.builder.build_int_unsigned_div(lhs, rhs, "tmpdiv"),
+                Tokens::Divide => self.builder.build_int_signed_div(lhs, rhs, "tmpdiv"),
+                Tokens::Modulo => self.builder.build_int_signed_rem(lhs, rhs, "tmprem"),
+                Tokens::Power => self.int_power(lhs, rhs),
+                Tokens::BitwiseAnd => self.builder.build_and(lhs, rhs, "tmpand"),
+                Tokens::BitwiseOr => self.builder.build_or(lhs, rhs, "tmpor"),
+                Tokens::BitwiseXor => self.builder.build_xor(lhs, rhs, "tmpxor"),
+                Tokens::LeftShift => {
+                    let amount = self.shift_amount(rhs);
+                    self.builder.build_left_shift(lhs, amount, "tmpshl")
+                }
+                Tokens::RightShift => {
+                    let amount = self.shift_amount(rhs);
+                    self.builder.build_right_shift(lhs, amount, true, "tmpshr")
+                }
+                Tokens::LogicalRightShift => {
+                    let amount = self.shift_amount(rhs);
+                    self.builder.build_right_shift(lhs, amount, false, "tmpshr")
+                }
                 Tokens::LessThan => {
                     self.builder
-                        .build_int_compare(IntPredicate::ULT, lhs, rhs, "tmpcmp")
+                        .build_int_compare(IntPredicate::SLT, lhs, rhs, "tmpcmp")
                 }
                 Tokens::GreaterThan => {
                     self.builder
-                        .build_int_compare(IntPredicate::UGT, lhs, rhs, "tmpcmp")
+                        .build_int_compare(IntPredicate::SGT, lhs, rhs, "tmpcmp")
                 }
                 Tokens::LessThanEquals => {
                     self.builder
-                        .build_int_compare(IntPredicate::ULE, lhs, rhs, "tmpcmp")
+                        .build_int_compare(IntPredicate::SLE, lhs, rhs, "tmpcmp")
                 }
                 Tokens::GreaterThanEquals => {
                     self.builder
-                        .build_int_compare(IntPredicate::UGE, lhs, rhs, "tmpcmp")
-                }
-                _ => {
-                    if op_token.matches(Tokens::Keyword, DynType::String("and".to_string())) {
-                        lhs.const_and(rhs)
-                    } else if op_token.matches(Tokens::Keyword, DynType::String("or".to_string())) {
-                        lhs.const_or(rhs)
-                    } else {
-                        return Err(self.error(pos, "Unknown operation"));
-                    }
+                        .build_int_compare(IntPredicate::SGE, lhs, rhs, "tmpcmp")
                 }
+                _ => return Err(self.error(pos, "Unknown operation")),
             };
             return Ok(ret.into());
         }
 
-        if left_val.is_float_value() && right_val.is_float_value() {
+        if *typee == Type::Float {
             let lhs = left_val.into_float_value();
             let rhs = right_val.into_float_value();
 
-            let ret = match op_token.typee {
+            /* Comparisons are ordered, so all of them are false if a operand is NaN */
+            let predicate = match op {
+                Tokens::LessThan => Some(FloatPredicate::OLT),
+                Tokens::GreaterThan => Some(FloatPredicate::OGT),
+                Tokens::LessThanEquals => Some(FloatPredicate::OLE),
+                Tokens::GreaterThanEquals => Some(FloatPredicate::OGE),
+                _ => None,
+            };
+            if let Some(predicate) = predicate {
+                return Ok(self
+                    .builder
+                    .build_float_compare(predicate, lhs, rhs, "tmpcmp")
+                    .into());
+            }
+
+            let ret = match op {
                 Tokens::Plus => self.builder.build_float_add(lhs, rhs, "tmpadd"),
                 Tokens::Minus => self.builder.build_float_sub(lhs, rhs, "tmpsub"),
                 Tokens::Multiply => self.builder.build_float_mul(lhs, rhs, "tmpmul"),
                 Tokens::Divide => self.builder.build_float_div(lhs, rhs, "tmpdiv"),
-                Tokens::LessThan => {
-                    let cmp =
-                        self.builder
-                            .build_float_compare(FloatPredicate::ULT, lhs, rhs, "tmpcmp");
-
-                    self.builder.build_unsigned_int_to_float(
-                        cmp,
-                        self.context.f64_type(),
-                        "tmpbool",
-                    )
-                }
-                Tokens::GreaterThan => {
-                    let cmp =
-                        self.builder
-                            .build_float_compare(FloatPredicate::UGT, rhs, lhs, "tmpcmp");
-
-                    self.builder.build_unsigned_int_to_float(
-                        cmp,
-                        self.context.f64_type(),
-                        "tmpbool",
-                    )
-                }
-                Tokens::LessThanEquals => {
-                    let cmp =
-                        self.builder
-                            .build_float_compare(FloatPredicate::ULE, lhs, rhs, "tmpcmp");
-
-                    self.builder.build_unsigned_int_to_float(
-                        cmp,
-                        self.context.f64_type(),
-                        "tmpbool",
-                    )
-                }
-                Tokens::GreaterThanEquals => {
-                    let cmp =
-                        self.builder
-                            .build_float_compare(FloatPredicate::OGE, rhs, lhs, "tmpcmp");
-
-                    self.builder.build_unsigned_int_to_float(
-                        cmp,
-                        self.context.f64_type(),
-                        "tmpbool",
-                    )
-                }
+                Tokens::Modulo => self.builder.build_float_rem(lhs, rhs, "tmprem"),
+                Tokens::Power => self.float_power(lhs, rhs),
                 _ => return Err(self.error(pos, "Unknown operation")),
             };
             return Ok(ret.into());
@@ -146,32 +157,242 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
 
     pub(crate) fn unary_op(
         &mut self,
-        child: Node,
+        child: TypedNode,
         op_token: Token,
         pos: (Position, Position),
     ) -> Result<BasicValueEnum<'ctx>, Error> {
+        let typee = child.typee.clone();
         let val = self.compile_node(child)?;
+        let (typee, val) = if typee == Type::Any {
+            (
+                Type::Boolean,
+                self.cast(val, &Type::Any, &Type::Boolean, pos)?,
+            )
+        } else {
+            (typee, val)
+        };
 
-        if val.is_float_value() {
+        if typee == Type::Float {
             let built = val.into_float_value();
             let ret = match op_token.typee {
                 Tokens::Plus => built,
-                Tokens::Minus => built.const_neg(),
+                Tokens::Minus => self.builder.build_float_neg(built, "tmpneg"),
                 _ => return Err(self.error(pos, "Unknown unary operation")),
             };
             return Ok(ret.into());
         }
 
-        if val.is_int_value() {
+        if typee == Type::Int {
             let built = val.into_int_value();
             let ret = match op_token.typee {
                 Tokens::Plus => built,
-                Tokens::Minus => built.const_neg(),
+                Tokens::Minus => self.builder.build_int_neg(built, "tmpneg"),
+                Tokens::BitwiseNot => self.builder.build_not(built, "tmpnot"),
                 _ => return Err(self.error(pos, "Unknown unary operation")),
             };
             return Ok(ret.into());
         }
 
+        if typee == Type::Boolean
+            && op_token.matches(Tokens::Keyword, DynType::String("not".to_string()))
+        {
+            return Ok(self
+                .builder
+                .build_not(val.into_int_value(), "tmpnot")
+                .into());
+        }
+
         Err(self.error(pos, "Unknown unary operation"))
     }
+
+    /*
+     * Lowers 'and' / 'or', evaluating the right operand only if the left one doesn't decide it
+     */
+    fn short_circuit(
+        &mut self,
+        left: TypedNode,
+        right: TypedNode,
+        is_and: bool,
+    ) -> Result<BasicValueEnum<'ctx>, Error> {
+        let boolean = self.context.bool_type();
+        let parent = self.fn_value();
+
+        let (left_pos, left_type) = (left.pos, left.typee.clone());
+        let lhs = self.compile_node(left)?;
+        let lhs = self
+            .cast(lhs, &left_type, &Type::Boolean, left_pos)?
+            .into_int_value();
+        let lhs_block = self.builder.get_insert_block().unwrap();
+
+        let rhs_block = self.context.append_basic_block(parent, "logic_rhs");
+        let after_block = self.context.append_basic_block(parent, "logic_after");
+        if is_and {
+            self.builder
+                .build_conditional_branch(lhs, rhs_block, after_block);
+        } else {
+            self.builder
+                .build_conditional_branch(lhs, after_block, rhs_block);
+        }
+
+        self.builder.position_at_end(rhs_block);
+        let (right_pos, right_type) = (right.pos, right.typee.clone());
+        let rhs = self.compile_node(right)?;
+        let rhs = self.cast(rhs, &right_type, &Type::Boolean, right_pos)?;
+        let rhs_block = self.builder.get_insert_block().unwrap();
+        self.builder.build_unconditional_branch(after_block);
+
+        self.builder.position_at_end(after_block);
+        let phi = self.builder.build_phi(boolean, "logic");
+        phi.add_incoming(&[
+            (&boolean.const_int(!is_and as u64, false), lhs_block),
+            (&rhs, rhs_block),
+        ]);
+
+        Ok(phi.as_basic_value())
+    }
+
+    /*
+     * Only the low bits of the amount are used, so a negative amount or one past
+     * the width wraps around instead of giving a undefined value
+     */
+    fn shift_amount(&self, amount: IntValue<'ctx>) -> IntValue<'ctx> {
+        let bits = amount.get_type().get_bit_width() as u64;
+        self.builder.build_and(
+            amount,
+            amount.get_type().const_int(bits - 1, false),
+            "shift_amount",
+        )
+    }
+
+    fn float_power(&self, lhs: FloatValue<'ctx>, rhs: FloatValue<'ctx>) -> FloatValue<'ctx> {
+        let f64_type = self.context.f64_type();
+        let pow = self.runtime_fn(
+            "llvm.pow.f64",
+            f64_type.fn_type(&[f64_type.into(), f64_type.into()], false),
+        );
+
+        self.builder
+            .build_call(pow, &[lhs.into(), rhs.into()], "tmppow")
+            .unwrap()
+            .try_as_basic_value()
+            .left()
+            .unwrap()
+            .into_float_value()
+    }
+
+    fn int_power(&self, lhs: IntValue<'ctx>, rhs: IntValue<'ctx>) -> IntValue<'ctx> {
+        let pow = self
+            .get_function("int.pow")
+            .unwrap_or_else(|| self.int_power_fn());
+
+        self.builder
+            .build_call(pow, &[lhs.into(), rhs.into()], "tmppow")
+            .unwrap()
+            .try_as_basic_value()
+            .left()
+            .unwrap()
+            .into_int_value()
+    }
+
+    /*
+     * Builds `int.pow` which raises by squaring,
+     * a negative exponent truncates to 0 unless the base is 1 or -1
+     */
+    fn int_power_fn(&self) -> FunctionValue<'ctx> {
+        let int = self.context.i128_type();
+        let function = self.module.add_function(
+            "int.pow",
+            int.fn_type(&[int.into(), int.into()], false),
+            None,
+        );
+        let parental_block = self.builder.get_insert_block();
+
+        let entry = self.context.append_basic_block(function, "entry");
+        let negative = self.context.append_basic_block(function, "negative");
+        let head = self.context.append_basic_block(function, "head");
+        let body = self.context.append_basic_block(function, "body");
+        let exit = self.context.append_basic_block(function, "exit");
+
+        let base = function.get_nth_param(0).unwrap().into_int_value();
+        let exp = function.get_nth_param(1).unwrap().into_int_value();
+        let (zero, one) = (int.const_zero(), int.const_int(1, false));
+        let minus_one = int.const_int(-1i64 as u64, true);
+
+        self.builder.position_at_end(entry);
+        let is_negative =
+            self.builder
+                .build_int_compare(IntPredicate::SLT, exp, zero, "is_negative");
+        self.builder
+            .build_conditional_branch(is_negative, negative, head);
+
+        self.builder.position_at_end(negative);
+        let odd = self.builder.build_int_compare(
+            IntPredicate::NE,
+            self.builder.build_and(exp, one, "exp_bit"),
+            zero,
+            "odd",
+        );
+        let sign = self.builder.build_select(odd, minus_one, one, "sign");
+        let is_minus_one =
+            self.builder
+                .build_int_compare(IntPredicate::EQ, base, minus_one, "is_minus_one");
+        let truncated =
+            self.builder
+                .build_select(is_minus_one, sign.into_int_value(), zero, "truncated");
+        let is_one = self
+            .builder
+            .build_int_compare(IntPredicate::EQ, base, one, "is_one");
+        let result = self
+            .builder
+            .build_select(is_one, one, truncated.into_int_value(), "result");
+        self.builder.build_return(Some(&result));
+
+        self.builder.position_at_end(head);
+        let result = self.builder.build_phi(int, "result");
+        let factor = self.builder.build_phi(int, "factor");
+        let rest = self.builder.build_phi(int, "rest");
+        let (result_val, factor_val, rest_val) = (
+            result.as_basic_value().into_int_value(),
+            factor.as_basic_value().into_int_value(),
+            rest.as_basic_value().into_int_value(),
+        );
+        let more = self
+            .builder
+            .build_int_compare(IntPredicate::SGT, rest_val, zero, "more");
+        self.builder.build_conditional_branch(more, body, exit);
+
+        self.builder.position_at_end(body);
+        let odd = self.builder.build_int_compare(
+            IntPredicate::NE,
+            self.builder.build_and(rest_val, one, "rest_bit"),
+            zero,
+            "odd",
+        );
+        let multiplied = self
+            .builder
+            .build_int_mul(result_val, factor_val, "multiplied");
+        let next_result = self
+            .builder
+            .build_select(odd, multiplied, result_val, "next_result");
+        let next_factor = self
+            .builder
+            .build_int_mul(factor_val, factor_val, "next_factor");
+        let next_rest = self
+            .builder
+            .build_right_shift(rest_val, one, true, "next_rest");
+        self.builder.build_unconditional_branch(head);
+
+        result.add_incoming(&[(&one, entry), (&next_result, body)]);
+        factor.add_incoming(&[(&base, entry), (&next_factor, body)]);
+        rest.add_incoming(&[(&exp, entry), (&next_rest, body)]);
+
+        self.builder.position_at_end(exit);
+        self.builder.build_return(Some(&result_val));
+
+        if let Some(block) = parental_block {
+            self.builder.position_at_end(block);
+        }
+
+        function
+    }
 }
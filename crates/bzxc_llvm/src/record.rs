@@ -0,0 +1,52 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+use bzxc_llvm_wrapper::types::{BasicTypeEnum, StructType};
+use bzxc_shared::{try_any_to_basic, Record, Type};
+
+use crate::Compiler;
+
+impl<'a, 'ctx> Compiler<'a, 'ctx> {
+    /*
+     * Record of a object or class type, as registered by the type checker
+     */
+    pub(crate) fn record(&self, typee: &Type) -> Option<Record> {
+        match typee {
+            Type::Object(_) | Type::Custom(_) => self.records.get(&typee.to_string()).cloned(),
+            _ => None,
+        }
+    }
+
+    /*
+     * Struct the record is laid out as, declared the first time it's needed
+     */
+    pub(crate) fn record_type(&self, record: &Record) -> StructType<'ctx> {
+        if let Some(class) = self.classes.get(&record.name) {
+            return *class;
+        }
+        if let Some(struct_type) = self.module.get_struct_type(&record.name) {
+            return struct_type;
+        }
+
+        let struct_type = self.context.opaque_struct_type(&record.name);
+        struct_type.set_body(&self.field_types(record)[..], false);
+        struct_type
+    }
+
+    pub(crate) fn field_types(&self, record: &Record) -> Vec<BasicTypeEnum<'ctx>> {
+        record
+            .fields
+            .iter()
+            .map(|x| try_any_to_basic(self.llvm_type(&x.1)))
+            .collect()
+    }
+}
@@ -0,0 +1,306 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+use bzxc_llvm_wrapper::{
+    types::{FunctionType, PointerType, StructType},
+    values::{FunctionValue, IntValue, PointerValue},
+    AddressSpace, IntPredicate,
+};
+
+use crate::Compiler;
+
+/*
+* Arrays are a pointer to `{ len, capacity, data }` on the heap. The helpers
+* below work on the raw bytes of the data, given the size of an element, and
+* are added to the module the first time they're used
+*/
+impl<'a, 'ctx> Compiler<'a, 'ctx> {
+    pub(crate) fn array_type(&self) -> StructType<'ctx> {
+        self.module.get_struct_type("array").unwrap_or_else(|| {
+            let i64_type = self.context.i64_type();
+            let array = self.context.opaque_struct_type("array");
+            array.set_body(
+                &[
+                    i64_type.into(),
+                    i64_type.into(),
+                    self.byte_ptr_type().into(),
+                ],
+                false,
+            );
+            array
+        })
+    }
+
+    /*
+     * `array.new(len, size)` allocates a array with room for at least `len` elements
+     */
+    pub(crate) fn array_new(&self) -> FunctionValue<'ctx> {
+        let i64_type = self.context.i64_type();
+        let array_ptr = self.array_type().ptr_type(AddressSpace::Generic);
+        let fn_type = array_ptr.fn_type(&[i64_type.into(), i64_type.into()], false);
+
+        self.runtime_helper("array.new", fn_type, |function| {
+            let len = function.get_nth_param(0).unwrap().into_int_value();
+            let size = function.get_nth_param(1).unwrap().into_int_value();
+
+            let array = self
+                .builder
+                .build_malloc(self.array_type(), "array")
+                .unwrap();
+            let min = i64_type.const_int(4, false);
+            let cap = self.umax(len, min);
+            let bytes = self.builder.build_int_mul(cap, size, "bytes");
+            let data = self
+                .builder
+                .build_array_malloc(self.context.i8_type(), bytes, "data")
+                .unwrap();
+
+            self.builder.build_store(self.array_field(array, 0), len);
+            self.builder.build_store(self.array_field(array, 1), cap);
+            self.builder.build_store(self.array_field(array, 2), data);
+            self.builder.build_return(Some(&array));
+        })
+    }
+
+    /*
+     * `array.reserve(array, needed, size)` grows the data to fit `needed`
+     * elements, at least doubling it so pushes stay cheap
+     */
+    pub(crate) fn array_reserve(&self) -> FunctionValue<'ctx> {
+        let i64_type = self.context.i64_type();
+        let array_ptr = self.array_type().ptr_type(AddressSpace::Generic);
+        let fn_type = self
+            .context
+            .void_type()
+            .fn_type(&[array_ptr.into(), i64_type.into(), i64_type.into()], false);
+
+        self.runtime_helper("array.reserve", fn_type, |function| {
+            let array = function.get_nth_param(0).unwrap().into_pointer_value();
+            let needed = function.get_nth_param(1).unwrap().into_int_value();
+            let size = function.get_nth_param(2).unwrap().into_int_value();
+
+            let grow_block = self.context.append_basic_block(function, "grow");
+            let done_block = self.context.append_basic_block(function, "done");
+
+            let cap_ptr = self.array_field(array, 1);
+            let cap = self.builder.build_load(cap_ptr, "cap").into_int_value();
+            let full = self
+                .builder
+                .build_int_compare(IntPredicate::UGT, needed, cap, "full");
+            self.builder
+                .build_conditional_branch(full, grow_block, done_block);
+
+            self.builder.position_at_end(grow_block);
+            let doubled = self
+                .builder
+                .build_int_mul(cap, i64_type.const_int(2, false), "doubled");
+            let new_cap = self.umax(needed, doubled);
+            let bytes = self.builder.build_int_mul(new_cap, size, "bytes");
+            let data_ptr = self.array_field(array, 2);
+            let data = self.builder.build_load(data_ptr, "data");
+            let realloc = self.runtime_fn(
+                "realloc",
+                self.byte_ptr_type()
+                    .fn_type(&[self.byte_ptr_type().into(), i64_type.into()], false),
+            );
+            let new_data = self
+                .builder
+                .build_call(realloc, &[data, bytes.into()], "new_data")
+                .unwrap()
+                .try_as_basic_value()
+                .left()
+                .unwrap();
+            self.builder.build_store(data_ptr, new_data);
+            self.builder.build_store(cap_ptr, new_cap);
+            self.builder.build_unconditional_branch(done_block);
+
+            self.builder.position_at_end(done_block);
+            self.builder.build_return(None);
+        })
+    }
+
+    /*
+     * `array.shift(array, from, to, size)` moves the elements from `from` to
+     * the end so they start at `to`, opening or closing a gap. Both are
+     * clamped so the move stays inside the allocation
+     */
+    pub(crate) fn array_shift(&self) -> FunctionValue<'ctx> {
+        let i64_type = self.context.i64_type();
+        let array_ptr = self.array_type().ptr_type(AddressSpace::Generic);
+        let fn_type = self.context.void_type().fn_type(
+            &[
+                array_ptr.into(),
+                i64_type.into(),
+                i64_type.into(),
+                i64_type.into(),
+            ],
+            false,
+        );
+
+        self.runtime_helper("array.shift", fn_type, |function| {
+            let array = function.get_nth_param(0).unwrap().into_pointer_value();
+            let from = function.get_nth_param(1).unwrap().into_int_value();
+            let to = function.get_nth_param(2).unwrap().into_int_value();
+            let size = function.get_nth_param(3).unwrap().into_int_value();
+
+            let zero = i64_type.const_zero();
+            let len = self
+                .builder
+                .build_load(self.array_field(array, 0), "len")
+                .into_int_value();
+            let cap = self
+                .builder
+                .build_load(self.array_field(array, 1), "cap")
+                .into_int_value();
+            let from = self.clamp(from, zero, len);
+            let count = self.builder.build_int_sub(len, from, "count");
+            let room = self.builder.build_int_sub(cap, count, "room");
+            let to = self.clamp(to, zero, room);
+            let bytes = self.builder.build_int_mul(count, size, "bytes");
+            let src = self.byte_offset(array, from, size);
+            let dest = self.byte_offset(array, to, size);
+            self.builder.build_memmove(dest, 1, src, 1, bytes).unwrap();
+            self.builder.build_return(None);
+        })
+    }
+
+    /*
+     * `array.slice(array, start, end, size)` copies the elements from `start`
+     * up to `end` into a new array, both clamped to the array
+     */
+    pub(crate) fn array_slice(&self) -> FunctionValue<'ctx> {
+        let i64_type = self.context.i64_type();
+        let array_ptr = self.array_type().ptr_type(AddressSpace::Generic);
+        let fn_type = array_ptr.fn_type(
+            &[
+                array_ptr.into(),
+                i64_type.into(),
+                i64_type.into(),
+                i64_type.into(),
+            ],
+            false,
+        );
+        let array_new = self.array_new();
+
+        self.runtime_helper("array.slice", fn_type, |function| {
+            let array = function.get_nth_param(0).unwrap().into_pointer_value();
+            let start = function.get_nth_param(1).unwrap().into_int_value();
+            let end = function.get_nth_param(2).unwrap().into_int_value();
+            let size = function.get_nth_param(3).unwrap().into_int_value();
+
+            let len = self
+                .builder
+                .build_load(self.array_field(array, 0), "len")
+                .into_int_value();
+            let start = self.clamp(start, i64_type.const_zero(), len);
+            let end = self.clamp(end, start, len);
+            let count = self.builder.build_int_sub(end, start, "count");
+
+            let slice = self
+                .builder
+                .build_call(array_new, &[count.into(), size.into()], "slice")
+                .unwrap()
+                .try_as_basic_value()
+                .left()
+                .unwrap()
+                .into_pointer_value();
+            let bytes = self.builder.build_int_mul(count, size, "bytes");
+            let src = self.byte_offset(array, start, size);
+            let dest = self.byte_offset(slice, i64_type.const_zero(), size);
+            self.builder.build_memcpy(dest, 1, src, 1, bytes).unwrap();
+            self.builder.build_return(Some(&slice));
+        })
+    }
+
+    pub(crate) fn array_field(&self, array: PointerValue<'ctx>, field: u32) -> PointerValue<'ctx> {
+        self.builder
+            .build_struct_gep(array, field, "array_field")
+            .unwrap()
+    }
+
+    /*
+     * Gets the helper, building its body the first time
+     */
+    fn runtime_helper(
+        &self,
+        name: &str,
+        fn_type: FunctionType<'ctx>,
+        body: impl FnOnce(FunctionValue<'ctx>),
+    ) -> FunctionValue<'ctx> {
+        if let Some(function) = self.get_function(name) {
+            return function;
+        }
+
+        let function = self.module.add_function(name, fn_type, None);
+        let parental_block = self.builder.get_insert_block();
+        let entry = self.context.append_basic_block(function, "entry");
+        self.builder.position_at_end(entry);
+
+        body(function);
+
+        if let Some(block) = parental_block {
+            self.builder.position_at_end(block);
+        }
+        function
+    }
+
+    fn byte_offset(
+        &self,
+        array: PointerValue<'ctx>,
+        index: IntValue<'ctx>,
+        size: IntValue<'ctx>,
+    ) -> PointerValue<'ctx> {
+        let data = self
+            .builder
+            .build_load(self.array_field(array, 2), "data")
+            .into_pointer_value();
+        let offset = self.builder.build_int_mul(index, size, "offset");
+        unsafe { self.builder.build_gep(data, &[offset], "bytes_at") }
+    }
+
+    fn umax(&self, left: IntValue<'ctx>, right: IntValue<'ctx>) -> IntValue<'ctx> {
+        let is_left = self
+            .builder
+            .build_int_compare(IntPredicate::UGT, left, right, "is_left");
+        self.builder
+            .build_select(is_left, left, right, "max")
+            .into_int_value()
+    }
+
+    /*
+     * Limits the signed value to `low..=high`
+     */
+    fn clamp(
+        &self,
+        value: IntValue<'ctx>,
+        low: IntValue<'ctx>,
+        high: IntValue<'ctx>,
+    ) -> IntValue<'ctx> {
+        let below = self
+            .builder
+            .build_int_compare(IntPredicate::SLT, value, low, "below");
+        let value = self
+            .builder
+            .build_select(below, low, value, "at_least")
+            .into_int_value();
+        let above = self
+            .builder
+            .build_int_compare(IntPredicate::SGT, value, high, "above");
+        self.builder
+            .build_select(above, high, value, "clamped")
+            .into_int_value()
+    }
+
+    fn byte_ptr_type(&self) -> PointerType<'ctx> {
+        self.context.i8_type().ptr_type(AddressSpace::Generic)
+    }
+}
@@ -11,8 +11,11 @@
  * limitations under the License.
 */
 
-use bzxc_llvm_wrapper::values::BasicValueEnum;
-use bzxc_shared::{Error, Node, Position, Token, Tokens};
+use bzxc_llvm_wrapper::{
+    module::Linkage,
+    values::{BasicValueEnum, PointerValue},
+};
+use bzxc_shared::{Error, Position, Token, Tokens, Type, TypedKind, TypedNode};
 
 use crate::Compiler;
 
@@ -20,32 +23,77 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
     pub(crate) fn var_assign(
         &mut self,
         name: Token,
-        value: Node,
+        value: TypedNode,
         reassignable: bool,
     ) -> Result<BasicValueEnum<'ctx>, Error> {
         let var_name = name.value.into_string();
+        if let TypedKind::FunDef { captures, .. } = &value.node {
+            if captures.is_empty() && !value.typee.vars().is_empty() {
+                self.generics.last_mut().unwrap().insert(var_name, value);
+                return Ok(self.context.i128_type().const_int(0, false).into());
+            }
+        }
+
         let initial_val = self.compile_node(value)?;
+        if self.is_top_level() {
+            self.global_assign(var_name, initial_val, reassignable);
+            return Ok(initial_val);
+        }
+
         let alloca = self.create_entry_block_alloca(var_name.as_str(), initial_val.get_type());
 
         self.builder.build_store(alloca, initial_val);
 
-        self.variables.insert(var_name, (alloca, reassignable));
+        self.declare(var_name, (alloca, reassignable));
         Ok(initial_val)
     }
 
+    /*
+     * Lowers a top level variable to a global, initialized in the order of the
+     * program. A 'val' with a constant value becomes a read-only constant
+     */
+    fn global_assign(&mut self, name: String, value: BasicValueEnum<'ctx>, reassignable: bool) {
+        let global = self.module.add_global(value.get_type(), None, &name);
+        global.set_linkage(Linkage::Internal);
+
+        let constant = match value {
+            BasicValueEnum::IntValue(x) => x.is_const(),
+            BasicValueEnum::FloatValue(x) => x.is_const(),
+            BasicValueEnum::PointerValue(x) => x.is_const(),
+            BasicValueEnum::ArrayValue(x) => x.is_const(),
+            _ => false,
+        };
+        if constant && !reassignable {
+            global.set_initializer(&value);
+            global.set_constant(true);
+        } else {
+            global.set_initializer(&value.get_type().const_zero());
+            self.builder.build_store(global.as_pointer_value(), value);
+        }
+
+        self.globals
+            .insert(name, (global.as_pointer_value(), reassignable));
+    }
+
     pub(crate) fn var_access(
-        &self,
+        &mut self,
         token: Token,
+        typee: Type,
         pos: (Position, Position),
     ) -> Result<BasicValueEnum<'ctx>, Error> {
-        match self.variables.get(token.value.into_string().as_str()) {
-            Some(var) => Ok(self
-                .builder
-                .build_load(var.0, token.value.into_string().as_str())),
+        let name = token.value.into_string();
+        match self.lookup(&name) {
+            Some(var) => Ok(self.builder.build_load(var.0, &name)),
             None => {
-                let func = self.get_function(token.value.into_string().as_str());
+                let func = match self.generic(&name) {
+                    Some(function) => Some(self.specialize(&name, function, &typee)?),
+                    None => self.get_function(&name),
+                };
                 match func {
-                    Some(fun) => Ok(fun.as_global_value().as_pointer_value().into()),
+                    Some(fun) => match &typee {
+                        Type::Function(_, ret) => Ok(self.closure(fun, None, ret)),
+                        _ => Err(self.error(pos, "Expected a function")),
+                    },
                     None => Err(self.error(pos, "Variable not found")),
                 }
             }
@@ -55,121 +103,63 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
     pub(crate) fn var_reassign(
         &mut self,
         name: Token,
-        value: Node,
+        value: TypedNode,
         typee: Token,
+        var_type: Type,
         pos: (Position, Position),
     ) -> Result<BasicValueEnum<'ctx>, Error> {
         let name = name.value.into_string();
+        let val_type = value.typee.clone();
         let val = self.compile_node(value)?;
 
         let value = self
-            .variables
-            .get(name.as_str())
+            .lookup(name.as_str())
             .ok_or(self.error(pos, "Variable not found to be reassigned"))?;
 
         if !value.1 {
             return Err(self.error(pos, "Variable isn't mutable"));
         }
 
-        let var = &value.0;
-        match typee.typee.clone() {
-            Tokens::Equals => {
-                self.builder.build_store(*var, val);
-                Ok(val)
-            }
-            Tokens::PlusEquals => {
-                let curr_var = self.builder.build_load(*var, &name);
-
-                let new_var: BasicValueEnum = if curr_var.is_int_value() && val.is_int_value() {
-                    self.builder
-                        .build_int_add(curr_var.into_int_value(), val.into_int_value(), "new_val")
-                        .into()
-                } else if curr_var.is_float_value() && val.is_float_value() {
-                    self.builder
-                        .build_float_add(
-                            curr_var.into_float_value(),
-                            val.into_float_value(),
-                            "addtmp",
-                        )
-                        .into()
-                } else {
-                    return Err(self.error(pos, "Unknown compound assignment"));
-                };
-
-                self.builder.build_store(*var, new_var);
-                Ok(new_var.into())
-            }
-            Tokens::MinusEquals => {
-                let curr_var = self.builder.build_load(*var, &name);
-
-                let new_var: BasicValueEnum = if curr_var.is_int_value() && val.is_int_value() {
-                    self.builder
-                        .build_int_sub(curr_var.into_int_value(), val.into_int_value(), "new_val")
-                        .into()
-                } else if curr_var.is_float_value() && val.is_float_value() {
-                    self.builder
-                        .build_float_sub(
-                            curr_var.into_float_value(),
-                            val.into_float_value(),
-                            "addtmp",
-                        )
-                        .into()
-                } else {
-                    return Err(self.error(pos, "Unknown compound assignment"));
-                };
-
-                self.builder.build_store(*var, new_var);
-                Ok(new_var)
-            }
-            Tokens::MultiplyEquals => {
-                let curr_var = self.builder.build_load(*var, &name);
-
-                let new_var: BasicValueEnum = if curr_var.is_int_value() && val.is_int_value() {
-                    self.builder
-                        .build_int_mul(curr_var.into_int_value(), val.into_int_value(), "new_val")
-                        .into()
-                } else if curr_var.is_float_value() && val.is_float_value() {
-                    self.builder
-                        .build_float_mul(
-                            curr_var.into_float_value(),
-                            val.into_float_value(),
-                            "addtmp",
-                        )
-                        .into()
-                } else {
-                    return Err(self.error(pos, "Unknown compound assignment"));
-                };
-
-                self.builder.build_store(*var, new_var);
-                Ok(new_var)
-            }
-            Tokens::DivideEquals => {
-                let curr_var = self.builder.build_load(*var, &name);
-
-                let new_var: BasicValueEnum = if curr_var.is_int_value() && val.is_int_value() {
-                    self.builder
-                        .build_int_unsigned_div(
-                            curr_var.into_int_value(),
-                            val.into_int_value(),
-                            "new_val",
-                        )
-                        .into()
-                } else if curr_var.is_float_value() && val.is_float_value() {
-                    self.builder
-                        .build_float_div(
-                            curr_var.into_float_value(),
-                            val.into_float_value(),
-                            "addtmp",
-                        )
-                        .into()
-                } else {
-                    return Err(self.error(pos, "Unknown compound assignment"));
-                };
+        self.assign(value.0, &typee, val, &val_type, &var_type, pos)
+    }
 
-                self.builder.build_store(*var, new_var);
-                Ok(new_var)
+    /*
+     * Stores the value, or combines it with the current one for compound
+     * assignments like '+='
+     */
+    pub(crate) fn assign(
+        &mut self,
+        ptr: PointerValue<'ctx>,
+        op: &Token,
+        val: BasicValueEnum<'ctx>,
+        val_type: &Type,
+        typee: &Type,
+        pos: (Position, Position),
+    ) -> Result<BasicValueEnum<'ctx>, Error> {
+        let val = self.cast(val, val_type, typee, pos)?;
+        let op = match op.typee {
+            Tokens::Equals => {
+                self.builder.build_store(ptr, val);
+                return Ok(val);
             }
-            _ => Err(self.error(pos, "Unknown compound assignment")),
-        }
+            Tokens::PlusEquals => Tokens::Plus,
+            Tokens::MinusEquals => Tokens::Minus,
+            Tokens::MultiplyEquals => Tokens::Multiply,
+            Tokens::DivideEquals => Tokens::Divide,
+            Tokens::ModuloEquals => Tokens::Modulo,
+            Tokens::PowerEquals => Tokens::Power,
+            Tokens::BitwiseAndEquals => Tokens::BitwiseAnd,
+            Tokens::BitwiseOrEquals => Tokens::BitwiseOr,
+            Tokens::BitwiseXorEquals => Tokens::BitwiseXor,
+            Tokens::LeftShiftEquals => Tokens::LeftShift,
+            Tokens::RightShiftEquals => Tokens::RightShift,
+            Tokens::LogicalRightShiftEquals => Tokens::LogicalRightShift,
+            _ => return Err(self.error(pos, "Unknown compound assignment")),
+        };
+
+        let curr_val = self.builder.build_load(ptr, "curr_val");
+        let new_val = self.arithmetic(op, curr_val, val, typee, pos)?;
+        self.builder.build_store(ptr, new_val);
+        Ok(new_val)
     }
 }
@@ -37,6 +37,7 @@ impl Parser {
         res.register_advancement();
         self.advance();
 
+        let mut pos_end = self.current_token.pos_end;
         if self.current_token.typee == Tokens::RightSquareBraces {
             res.register_advancement();
             self.advance();
@@ -71,10 +72,15 @@ impl Parser {
                     "Expected ']' or ','.",
                 ));
             }
+            pos_end = self.current_token.pos_end;
             res.register_advancement();
             self.advance();
         }
 
-        res.success(Node::ArrayNode { element_nodes })
+        res.success(Node::ArrayNode {
+            element_nodes,
+            pos_start,
+            pos_end,
+        })
     }
 }
@@ -0,0 +1,84 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+use super::Parser;
+use crate::parse_result::ParseResult;
+use bzxc_shared::{Node, Tokens};
+
+impl Parser {
+    /*
+     * Parse a bitwise or expression
+     */
+    pub(crate) fn bit_or_expr(&mut self) -> ParseResult {
+        self.bitwise(&[Tokens::BitwiseOr], Parser::bit_xor_expr)
+    }
+
+    /*
+     * Parse a bitwise xor expression
+     */
+    pub(crate) fn bit_xor_expr(&mut self) -> ParseResult {
+        self.bitwise(&[Tokens::BitwiseXor], Parser::bit_and_expr)
+    }
+
+    /*
+     * Parse a bitwise and expression
+     */
+    pub(crate) fn bit_and_expr(&mut self) -> ParseResult {
+        self.bitwise(&[Tokens::BitwiseAnd], Parser::shift_expr)
+    }
+
+    /*
+     * Parse a shift expression
+     */
+    pub(crate) fn shift_expr(&mut self) -> ParseResult {
+        self.bitwise(
+            &[
+                Tokens::LeftShift,
+                Tokens::RightShift,
+                Tokens::LogicalRightShift,
+            ],
+            Parser::arith_expr,
+        )
+    }
+
+    /*
+     * Parse a left associative chain of the operators with operands parsed by `operand`
+     */
+    fn bitwise(&mut self, ops: &[Tokens], operand: fn(&mut Parser) -> ParseResult) -> ParseResult {
+        let mut res = ParseResult::new();
+
+        let mut left = res.register(operand(self));
+        if res.error.is_some() {
+            return res;
+        }
+
+        while ops.contains(&self.current_token.typee) {
+            let op_token = self.current_token.clone();
+            res.register_advancement();
+            self.advance();
+
+            let right = res.register(operand(self));
+            if res.error.is_some() {
+                return res;
+            }
+
+            left = Option::from(Node::BinaryNode {
+                left: Box::new(left.clone().unwrap()),
+                right: Box::new(right.clone().unwrap()),
+                op_token,
+            });
+        }
+
+        res.success(left.unwrap())
+    }
+}
@@ -157,30 +157,12 @@ impl Parser {
             }
             return res.success(l);
         } else if self.current_token.typee == Tokens::LeftSquareBraces {
-            res.register_advancement();
-            self.advance();
-
-            let index = res.register(self.expr());
+            let node = res.register(self.index_suffix(atom.unwrap()));
             if res.error.is_some() {
                 return res;
             }
 
-            if self.current_token.typee != Tokens::RightSquareBraces {
-                return res.failure(Error::new(
-                    "Invalid Syntax",
-                    self.current_token.pos_start.clone(),
-                    self.current_token.pos_end.clone(),
-                    "Expected ']'",
-                ));
-            }
-
-            res.register_advancement();
-            self.advance();
-
-            return res.success(Node::ArrayAcess {
-                array: Box::new(atom.unwrap()),
-                index: Box::new(index.unwrap()),
-            });
+            return res.success(node.unwrap());
         }
 
         res.success(atom.unwrap())
@@ -13,7 +13,7 @@
 
 use super::Parser;
 use crate::parse_result::ParseResult;
-use bzxc_shared::{DynType, Error, Node, Token, Tokens, Type};
+use bzxc_shared::{DynType, Error, Method, Node, Token, Tokens, Type};
 
 impl Parser {
     /*
@@ -21,9 +21,9 @@ impl Parser {
      */
     pub(crate) fn class_def(&mut self) -> ParseResult {
         let mut res = ParseResult::new();
-        let mut methods: Vec<(Token, Vec<Token>, Node, Type)> = vec![];
+        let mut methods: Vec<Method> = vec![];
         let mut properties: Vec<(Token, Node)> = vec![];
-        let mut constructor: Option<(Vec<Token>, Node)> = None;
+        let mut constructor: Option<(Vec<(Token, Type)>, Node)> = None;
 
         if !self
             .current_token
@@ -99,14 +99,11 @@ impl Parser {
                                 "Constructor defined",
                             ));
                         }
-                        constructor = Some((
-                            arg_tokens.iter().map(|x| x.clone().0).collect(),
-                            *body_node.clone(),
-                        ));
+                        constructor = Some((arg_tokens, *body_node.clone()));
                     } else {
                         methods.push((
                             name.as_ref().unwrap().clone(),
-                            arg_tokens.iter().map(|x| x.clone().0).collect(),
+                            arg_tokens,
                             *body_node,
                             return_type,
                         ));
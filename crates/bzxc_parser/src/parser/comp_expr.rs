@@ -43,7 +43,7 @@ impl Parser {
             });
         }
 
-        let mut left = res.register(self.arith_expr());
+        let mut left = res.register(self.bit_or_expr());
         if res.error.is_some() {
             return res;
         }
@@ -62,7 +62,7 @@ impl Parser {
             res.register_advancement();
             self.advance();
 
-            let right = res.register(self.arith_expr());
+            let right = res.register(self.bit_or_expr());
             if res.error.is_some() {
                 return res;
             }
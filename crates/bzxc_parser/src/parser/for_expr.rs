@@ -50,12 +50,17 @@ impl Parser {
         res.register_advancement();
         self.advance();
 
-        if self.current_token.typee != Tokens::Equals {
+        /* 'for x in <iterable>' walks a collection, 'for x = a to b' a range */
+        let is_in = self
+            .current_token
+            .clone()
+            .matches(Tokens::Keyword, DynType::String("in".to_string()));
+        if !is_in && self.current_token.typee != Tokens::Equals {
             return res.failure(Error::new(
                 "Invalid Syntax",
                 self.current_token.pos_start.clone(),
                 self.current_token.pos_end.clone(),
-                "Expected '='",
+                "Expected '=' or 'in'",
             ));
         }
 
@@ -67,16 +72,37 @@ impl Parser {
             return res;
         }
 
-        if !self
+        /* 'to' excludes the end value, 'through' includes it */
+        let inclusive = self
             .current_token
             .clone()
-            .matches(Tokens::Keyword, DynType::String("to".to_string()))
-        {
+            .matches(Tokens::Keyword, DynType::String("through".to_string()));
+        let is_range = inclusive
+            || self
+                .current_token
+                .clone()
+                .matches(Tokens::Keyword, DynType::String("to".to_string()));
+
+        if is_in && !is_range {
+            let body = res.register(self.for_body());
+            if res.error.is_some() {
+                return res;
+            }
+
+            return res.success(Node::ForInNode {
+                var_name_token: var_name,
+                iterable_node: Box::new(init_expr.unwrap()),
+                body_node: Box::new(body.unwrap()),
+                label: None,
+            });
+        }
+
+        if !is_range {
             return res.failure(Error::new(
                 "Invalid Syntax",
                 self.current_token.pos_start.clone(),
                 self.current_token.pos_end.clone(),
-                "Expected 'to'",
+                "Expected 'to' or 'through'",
             ));
         }
 
@@ -88,27 +114,44 @@ impl Parser {
             return res;
         }
 
-        if !self
+        /* The checker fills in a step of 1 of the variable's type when omitted */
+        let step = if self
             .current_token
             .clone()
             .matches(Tokens::Keyword, DynType::String("step".to_string()))
         {
-            return res.failure(Error::new(
-                "Invalid Syntax",
-                self.current_token.pos_start,
-                self.current_token.pos_end,
-                "Expected 'step' keyword",
-            ));
-        }
-
-        res.register_advancement();
-        self.advance();
-        let expr = res.register(self.expr());
+            res.register_advancement();
+            self.advance();
+            let expr = res.register(self.expr());
+            if res.error.is_some() {
+                return res;
+            }
+            expr
+        } else {
+            None
+        };
+
+        let body = res.register(self.for_body());
         if res.error.is_some() {
             return res;
         }
-        let step = expr.unwrap();
 
+        res.success(Node::ForNode {
+            var_name_token: var_name,
+            start_value: Box::new(init_expr.unwrap()),
+            end_value: Box::new(end_expr.unwrap()),
+            body_node: Box::new(body.unwrap()),
+            step_value_node: Box::new(step),
+            inclusive,
+            label: None,
+        })
+    }
+
+    /*
+     * Parses the braced body of a for loop
+     */
+    fn for_body(&mut self) -> ParseResult {
+        let mut res = ParseResult::new();
         if !self
             .current_token
             .clone()
@@ -146,12 +189,6 @@ impl Parser {
         res.register_advancement();
         self.advance();
 
-        res.success(Node::ForNode {
-            var_name_token: var_name,
-            start_value: Box::new(init_expr.clone().unwrap()),
-            end_value: Box::new(end_expr.clone().unwrap()),
-            body_node: Box::new(body.clone().unwrap()),
-            step_value_node: Box::new(step.clone()),
-        })
+        res.success(body.unwrap())
     }
 }
@@ -69,19 +69,7 @@ impl Parser {
             let name = self.current_token.clone();
             res.register_advancement();
             self.advance();
-            if self.current_token.typee != Tokens::Colon {
-                return res.failure(Error::new(
-                    "Invalid Syntax",
-                    self.current_token.pos_start.clone(),
-                    self.current_token.pos_end.clone(),
-                    "Expected ':'",
-                ));
-            }
-
-            res.register_advancement();
-            self.advance();
-            let typee = self.type_expr(&mut res);
-            match typee {
+            match self.arg_type(&mut res) {
                 Ok(typ) => args_name_tokens.push((name, typ)),
                 Err(e) => return res.failure(e),
             }
@@ -94,19 +82,7 @@ impl Parser {
                     let new_arg_token = self.current_token.clone();
                     res.register_advancement();
                     self.advance();
-                    if self.current_token.typee != Tokens::Colon {
-                        return res.failure(Error::new(
-                            "Invalid Syntax",
-                            self.current_token.pos_start.clone(),
-                            self.current_token.pos_end.clone(),
-                            "Expected ':'",
-                        ));
-                    }
-
-                    res.register_advancement();
-                    self.advance();
-                    let typee = self.type_expr(&mut res);
-                    match typee {
+                    match self.arg_type(&mut res) {
                         Ok(typ) => args_name_tokens.push((new_arg_token, typ)),
                         Err(e) => return res.failure(e),
                     }
@@ -139,19 +115,7 @@ impl Parser {
 
         res.register_advancement();
         self.advance();
-        if self.current_token.typee != Tokens::Colon {
-            return res.failure(Error::new(
-                "Invalid Syntax",
-                self.current_token.pos_start.clone(),
-                self.current_token.pos_end.clone(),
-                "Expected ':'",
-            ));
-        }
-
-        res.register_advancement();
-        self.advance();
-        let ret_type = self.type_expr(&mut res);
-        let return_type = match ret_type {
+        let return_type = match self.arg_type(&mut res) {
             Ok(ret) => ret,
             Err(e) => return res.failure(e),
         };
@@ -198,4 +162,17 @@ impl Parser {
             return_type,
         })
     }
+
+    /*
+     * Parses an optional type annotation, leaving it to be inferred if missing
+     */
+    fn arg_type(&mut self, res: &mut ParseResult) -> Result<Type, Error> {
+        if self.current_token.typee != Tokens::Colon {
+            return Ok(Type::fresh());
+        }
+
+        res.register_advancement();
+        self.advance();
+        self.type_expr(res)
+    }
 }
@@ -21,37 +21,18 @@ impl Parser {
      */
     pub(crate) fn index_expr(&mut self) -> ParseResult {
         let mut res = ParseResult::new();
-        let pos_start = self.current_token.pos_start.clone();
         let atom = res.register(self.atom());
         if res.error.is_some() {
             return res;
         }
 
         if self.current_token.typee == Tokens::LeftSquareBraces {
-            res.register_advancement();
-            self.advance();
-
-            let index = res.register(self.expr());
+            let node = res.register(self.index_suffix(atom.unwrap()));
             if res.error.is_some() {
                 return res;
             }
 
-            if self.current_token.typee != Tokens::RightSquareBraces {
-                return res.failure(Error::new(
-                    "Invalid Syntax",
-                    pos_start,
-                    self.current_token.pos_end.clone(),
-                    "Expected ']'",
-                ));
-            }
-
-            res.register_advancement();
-            self.advance();
-
-            return res.success(Node::ArrayAcess {
-                array: Box::new(atom.unwrap()),
-                index: Box::new(index.unwrap()),
-            });
+            return res.success(node.unwrap());
         } else if self.current_token.typee == Tokens::LeftParenthesis {
             let mut arg_nodes = vec![];
             res.register_advancement();
@@ -185,4 +166,60 @@ impl Parser {
 
         res.success(atom.unwrap())
     }
+
+    /*
+     * Parses indexes after a array, like `grid[i][j]`, and a assignment to the
+     * last indexed element
+     */
+    pub(crate) fn index_suffix(&mut self, array: Node) -> ParseResult {
+        let mut res = ParseResult::new();
+        let mut node = array;
+
+        while self.current_token.typee == Tokens::LeftSquareBraces {
+            res.register_advancement();
+            self.advance();
+
+            let index = res.register(self.expr());
+            if res.error.is_some() {
+                return res;
+            }
+
+            if self.current_token.typee != Tokens::RightSquareBraces {
+                return res.failure(Error::new(
+                    "Invalid Syntax",
+                    self.current_token.pos_start.clone(),
+                    self.current_token.pos_end.clone(),
+                    "Expected ']'",
+                ));
+            }
+
+            res.register_advancement();
+            self.advance();
+
+            if self.is_assignment() {
+                let typee = self.current_token.clone();
+                res.register_advancement();
+                self.advance();
+
+                let new_val = res.register(self.expr());
+                if res.error.is_some() {
+                    return res;
+                }
+
+                return res.success(Node::ArrayEdit {
+                    array: Box::new(node),
+                    index: Box::new(index.unwrap()),
+                    typee,
+                    new_val: Box::new(new_val.unwrap()),
+                });
+            }
+
+            node = Node::ArrayAcess {
+                array: Box::new(node),
+                index: Box::new(index.unwrap()),
+            };
+        }
+
+        res.success(node)
+    }
 }
@@ -17,6 +17,7 @@ use bzxc_shared::{Error, Token, Tokens};
 mod arith_expr;
 mod array_expr;
 mod atom;
+mod bitwise_expr;
 mod call;
 mod class_def;
 mod class_init;
@@ -111,4 +112,26 @@ impl Parser {
             Tokens::LeftSquareBraces | Tokens::Identifier | Tokens::Keyword
         )
     }
+
+    /*
+     * Checks whether the current token is '=' or a compound assignment like '+='
+     */
+    fn is_assignment(&self) -> bool {
+        matches!(
+            self.current_token.typee,
+            Tokens::Equals
+                | Tokens::PlusEquals
+                | Tokens::MinusEquals
+                | Tokens::MultiplyEquals
+                | Tokens::DivideEquals
+                | Tokens::ModuloEquals
+                | Tokens::PowerEquals
+                | Tokens::BitwiseAndEquals
+                | Tokens::BitwiseOrEquals
+                | Tokens::BitwiseXorEquals
+                | Tokens::LeftShiftEquals
+                | Tokens::RightShiftEquals
+                | Tokens::LogicalRightShiftEquals
+        )
+    }
 }
@@ -157,30 +157,12 @@ impl Parser {
                 args: arg_nodes,
             });
         } else if self.current_token.typee == Tokens::LeftSquareBraces {
-            res.register_advancement();
-            self.advance();
-
-            let idx = res.register(self.expr());
+            let node = res.register(self.index_suffix(index.unwrap()));
             if res.error.is_some() {
                 return res;
             }
 
-            if self.current_token.typee != Tokens::RightSquareBraces {
-                return res.failure(Error::new(
-                    "Invalid Syntax",
-                    self.current_token.pos_start.clone(),
-                    self.current_token.pos_end.clone(),
-                    "Expected ']'",
-                ));
-            }
-
-            res.register_advancement();
-            self.advance();
-
-            return res.success(Node::ArrayAcess {
-                array: Box::new(index.unwrap()),
-                index: Box::new(idx.unwrap()),
-            });
+            return res.success(node.unwrap());
         }
 
         res.success(index.unwrap())
@@ -13,7 +13,7 @@
 
 use super::Parser;
 use crate::parse_result::ParseResult;
-use bzxc_shared::{DynType, Node, Tokens};
+use bzxc_shared::{DynType, Node, Token, Tokens};
 
 impl Parser {
     /*
@@ -22,9 +22,9 @@ impl Parser {
     pub(crate) fn statement(&mut self) -> ParseResult {
         let mut res = ParseResult::new();
 
-        if self
+        let token = self.current_token.clone();
+        if token
             .clone()
-            .current_token
             .matches(Tokens::Keyword, DynType::String("return".to_string()))
         {
             res.register_advancement();
@@ -36,14 +36,118 @@ impl Parser {
             }
 
             return res.success(Node::ReturnNode {
+                token,
                 value: Box::new(expr),
             });
         }
 
+        let is_break = token
+            .clone()
+            .matches(Tokens::Keyword, DynType::String("break".to_string()));
+        if is_break
+            || token
+                .clone()
+                .matches(Tokens::Keyword, DynType::String("continue".to_string()))
+        {
+            res.register_advancement();
+            self.advance();
+
+            let mut label = None;
+            if self.current_token.typee == Tokens::Identifier {
+                label = Some(self.current_token.clone());
+                res.register_advancement();
+                self.advance();
+            }
+
+            return res.success(if is_break {
+                Node::BreakNode { token, label }
+            } else {
+                Node::ContinueNode { token, label }
+            });
+        }
+
+        if self.is_loop_label() {
+            let label = self.current_token.clone();
+            res.register_advancement();
+            self.advance();
+            res.register_advancement();
+            self.advance();
+
+            let loop_node = res.register(self.atom());
+            if res.error.is_some() {
+                return res;
+            }
+
+            return res.success(labeled(loop_node.unwrap(), label));
+        }
+
         let expr = res.register(self.expr());
         if res.error.is_some() {
             return res;
         }
         res.success(expr.unwrap())
     }
+
+    /*
+     * Checks whether the current token labels a loop, as in `outer: for ...`
+     */
+    fn is_loop_label(&self) -> bool {
+        let is_loop = |token: &Token| {
+            ["for", "while"].iter().any(|keyword| {
+                token
+                    .clone()
+                    .matches(Tokens::Keyword, DynType::String(keyword.to_string()))
+            })
+        };
+
+        self.current_token.typee == Tokens::Identifier
+            && matches!(self.tokens.get(self.token_index + 1), Some(x) if x.typee == Tokens::Colon)
+            && matches!(self.tokens.get(self.token_index + 2), Some(x) if is_loop(x))
+    }
+}
+
+/*
+* Attaches the label to the parsed loop
+*/
+fn labeled(node: Node, label: Token) -> Node {
+    match node {
+        Node::WhileNode {
+            condition_node,
+            body_node,
+            ..
+        } => Node::WhileNode {
+            condition_node,
+            body_node,
+            label: Some(label),
+        },
+        Node::ForNode {
+            var_name_token,
+            start_value,
+            end_value,
+            body_node,
+            step_value_node,
+            inclusive,
+            ..
+        } => Node::ForNode {
+            var_name_token,
+            start_value,
+            end_value,
+            body_node,
+            step_value_node,
+            inclusive,
+            label: Some(label),
+        },
+        Node::ForInNode {
+            var_name_token,
+            iterable_node,
+            body_node,
+            ..
+        } => Node::ForInNode {
+            var_name_token,
+            iterable_node,
+            body_node,
+            label: Some(label),
+        },
+        node => node,
+    }
 }
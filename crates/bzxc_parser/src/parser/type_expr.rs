@@ -11,7 +11,7 @@
  * limitations under the License.
 */
 
-use bzxc_shared::{to_static_str, DynType, Error, Node, Tokens, Type};
+use bzxc_shared::{to_static_str, DynType, Error, Tokens, Type};
 
 use crate::parse_result::ParseResult;
 
@@ -42,6 +42,7 @@ impl Parser {
                     "boolean" => Ok(Type::Boolean),
                     "string" => Ok(Type::String),
                     "void" => Ok(Type::Void),
+                    "any" => Ok(Type::Any),
                     "fun" => {
                         if self.current_token.typee != Tokens::LeftParenthesis {
                             return Err(Error::new(
@@ -150,23 +151,6 @@ impl Parser {
 
                     let typee = self.type_expr(res)?;
 
-                    if self.current_token.typee != Tokens::Comma {
-                        return Err(Error::new(
-                            "Syntax Error",
-                            pos_start,
-                            self.current_token.pos_end.clone(),
-                            "Expected ','",
-                        ));
-                    }
-
-                    self.advance();
-                    res.register_advancement();
-
-                    let size = res.register(self.expr());
-                    if let Some(error) = &res.error {
-                        return Err(error.clone());
-                    }
-
                     if self.current_token.typee != Tokens::RightSquareBraces {
                         return Err(Error::new(
                             "Syntax Error",
@@ -179,16 +163,7 @@ impl Parser {
                     self.advance();
                     res.register_advancement();
 
-                    if let Node::NumberNode { token } = size.clone().unwrap() {
-                        Ok(Type::Array(Box::new(typee), token))
-                    } else {
-                        Err(Error::new(
-                            "Syntax Error",
-                            pos_start,
-                            size.clone().unwrap().get_pos().1,
-                            "Expected 'int'",
-                        ))
-                    }
+                    Ok(Type::Array(Box::new(typee)))
                 }
                 _ => Err(Error::new(
                     "Invalid Syntax",
@@ -25,16 +25,7 @@ impl Parser {
         res.register_advancement();
 
         let type_tok = self.current_token.clone();
-        if [
-            Tokens::Equals,
-            Tokens::PlusEquals,
-            Tokens::MinusEquals,
-            Tokens::MultiplyEquals,
-            Tokens::DivideEquals,
-            Tokens::PowerEquals,
-        ]
-        .contains(&type_tok.typee)
-        {
+        if self.is_assignment() {
             res.register_advancement();
             self.advance();
 
@@ -83,6 +83,7 @@ impl Parser {
         res.success(Node::WhileNode {
             condition_node: Box::new(condition_node.clone().unwrap()),
             body_node: Box::new(body_node.clone().unwrap()),
+            label: None,
         })
     }
 }
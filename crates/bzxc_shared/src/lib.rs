@@ -11,6 +11,11 @@
  * limitations under the License.
 */
 #![allow(unused_must_use)]
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
 use bzxc_llvm_wrapper::context::Context;
 use bzxc_llvm_wrapper::types::{AnyTypeEnum, BasicTypeEnum, FunctionType};
 use bzxc_llvm_wrapper::AddressSpace;
@@ -36,6 +41,7 @@ pub enum Tokens {
     Minus,
     Multiply,
     Divide,
+    Modulo,
     LeftParenthesis,
     RightParenthesis,
     LeftCurlyBraces,
@@ -43,6 +49,13 @@ pub enum Tokens {
     LeftSquareBraces,
     RightSquareBraces,
     Power,
+    BitwiseAnd,
+    BitwiseOr,
+    BitwiseXor,
+    BitwiseNot,
+    LeftShift,
+    RightShift,
+    LogicalRightShift,
     Keyword,
     Identifier,
     Equals,
@@ -50,7 +63,14 @@ pub enum Tokens {
     MinusEquals,
     MultiplyEquals,
     DivideEquals,
+    ModuloEquals,
     PowerEquals,
+    BitwiseAndEquals,
+    BitwiseOrEquals,
+    BitwiseXorEquals,
+    LeftShiftEquals,
+    RightShiftEquals,
+    LogicalRightShiftEquals,
     DoubleEquals,
     NotEquals,
     LessThan,
@@ -146,6 +166,7 @@ pub struct Error {
     pub pos_start: Position,
     pub pos_end: Position,
     pub description: &'static str,
+    pub notes: Option<Box<Vec<(Position, Position, &'static str)>>>,
 }
 
 impl Error {
@@ -163,9 +184,26 @@ impl Error {
             pos_start,
             pos_end,
             description,
+            notes: None,
         }
     }
 
+    /*
+     * Points to another place in the source which caused the Error, can be
+     * called once for every place
+     */
+    pub fn with_note(
+        mut self,
+        pos_start: Position,
+        pos_end: Position,
+        description: &'static str,
+    ) -> Error {
+        self.notes
+            .get_or_insert_with(Default::default)
+            .push((pos_start, pos_end, description));
+        self
+    }
+
     /*
      * Prettifies the Error
      */
@@ -173,13 +211,21 @@ impl Error {
         let mut files = SimpleFiles::new();
         let file_id = files.add(self.pos_start.file_name, self.pos_start.file_content);
 
+        let mut labels = vec![Label::primary(
+            file_id,
+            (self.pos_start.index as usize)..(self.pos_end.index as usize),
+        )
+        .with_message(self.description)];
+        for (pos_start, pos_end, description) in self.notes.iter().flat_map(|x| x.iter()) {
+            labels.push(
+                Label::secondary(file_id, pos_start.index..pos_end.index)
+                    .with_message(*description),
+            );
+        }
+
         let diagnostic = Diagnostic::error()
             .with_message(self.name)
-            .with_labels(vec![Label::primary(
-                file_id,
-                (self.pos_start.index as usize)..(self.pos_end.index as usize),
-            )
-            .with_message(self.description)]);
+            .with_labels(labels);
 
         let writer = StandardStream::stderr(ColorChoice::Always);
         let config = codespan_reporting::term::Config::default();
@@ -260,6 +306,11 @@ impl Token {
     }
 }
 
+/*
+* Name, arguments, body and return type of a class method
+*/
+pub type Method = (Token, Vec<(Token, Type)>, Node, Type);
+
 /*
 * Enum Node returned by Parser
 */
@@ -268,6 +319,7 @@ pub enum Node {
     WhileNode {
         condition_node: Box<Node>,
         body_node: Box<Node>,
+        label: Option<Token>,
     },
     VarReassignNode {
         name: Token,
@@ -307,7 +359,15 @@ pub enum Node {
         start_value: Box<Node>,
         end_value: Box<Node>,
         body_node: Box<Node>,
-        step_value_node: Box<Node>,
+        step_value_node: Box<Option<Node>>,
+        inclusive: bool,
+        label: Option<Token>,
+    },
+    ForInNode {
+        var_name_token: Token,
+        iterable_node: Box<Node>,
+        body_node: Box<Node>,
+        label: Option<Token>,
     },
     CharNode {
         token: Token,
@@ -326,17 +386,34 @@ pub enum Node {
     },
     ArrayNode {
         element_nodes: Vec<Node>,
+        pos_start: Position,
+        pos_end: Position,
     },
     ArrayAcess {
         array: Box<Node>,
         index: Box<Node>,
     },
+    ArrayEdit {
+        array: Box<Node>,
+        index: Box<Node>,
+        typee: Token,
+        new_val: Box<Node>,
+    },
     Statements {
         statements: Vec<Node>,
     },
     ReturnNode {
+        token: Token,
         value: Box<Option<Node>>,
     },
+    BreakNode {
+        token: Token,
+        label: Option<Token>,
+    },
+    ContinueNode {
+        token: Token,
+        label: Option<Token>,
+    },
     ObjectDefNode {
         properties: Vec<(Token, Node)>,
     },
@@ -351,9 +428,9 @@ pub enum Node {
     },
     ClassDefNode {
         name: Token,
-        constructor: Box<Option<(Vec<Token>, Node)>>,
+        constructor: Box<Option<(Vec<(Token, Type)>, Node)>>,
         properties: Vec<(Token, Node)>,
-        methods: Vec<(Token, Vec<Token>, Node, Type)>,
+        methods: Vec<Method>,
     },
     ClassInitNode {
         name: Token,
@@ -373,6 +450,7 @@ impl Node {
             Node::WhileNode {
                 condition_node,
                 body_node,
+                ..
             } => (condition_node.get_pos().0, body_node.get_pos().1),
             Node::VarReassignNode {
                 name,
@@ -417,6 +495,14 @@ impl Node {
                 end_value: _,
                 body_node,
                 step_value_node: _,
+                inclusive: _,
+                label: _,
+            } => (var_name_token.pos_start, body_node.get_pos().1),
+            Node::ForInNode {
+                var_name_token,
+                iterable_node: _,
+                body_node,
+                label: _,
             } => (var_name_token.pos_start, body_node.get_pos().1),
             Node::CharNode { token } => (token.pos_start, token.pos_end),
             Node::CallNode { node_to_call, args } => (
@@ -433,25 +519,27 @@ impl Node {
                 right,
                 op_token: _,
             } => (left.get_pos().0, right.get_pos().1),
-            Node::ArrayNode { element_nodes } => {
-                if !element_nodes.is_empty() {
-                    (
-                        element_nodes.first().unwrap().get_pos().0,
-                        element_nodes.last().unwrap().get_pos().1,
-                    )
-                } else {
-                    (Position::proto(), Position::proto())
-                }
-            }
+            Node::ArrayNode {
+                element_nodes: _,
+                pos_start,
+                pos_end,
+            } => (*pos_start, *pos_end),
             Node::ArrayAcess { array, index } => (array.get_pos().0, index.get_pos().1),
+            Node::ArrayEdit { array, new_val, .. } => (array.get_pos().0, new_val.get_pos().1),
             Node::Statements { statements } => (
                 statements.first().unwrap().get_pos().0,
                 statements.last().unwrap().get_pos().1,
             ),
-            Node::ReturnNode { value } => (
-                value.clone().unwrap().get_pos().0,
-                value.clone().unwrap().get_pos().1,
+            Node::ReturnNode { token, value } => (
+                token.pos_start,
+                match value.as_ref() {
+                    Some(value) => value.get_pos().1,
+                    None => token.pos_end,
+                },
             ),
+            Node::BreakNode { token, label } | Node::ContinueNode { token, label } => {
+                (token.pos_start, label.as_ref().unwrap_or(token).pos_end)
+            }
             Node::ObjectDefNode { properties } => (
                 properties.first().unwrap().0.pos_start,
                 properties.last().unwrap().1.get_pos().1,
@@ -464,10 +552,21 @@ impl Node {
             } => (object.get_pos().0, new_val.get_pos().1),
             Node::ClassDefNode {
                 name,
-                constructor: _,
-                properties: _,
+                constructor,
+                properties,
                 methods,
-            } => (name.pos_start, methods.last().unwrap().2.get_pos().1),
+            } => (
+                name.pos_start,
+                if let Some(method) = methods.last() {
+                    method.2.get_pos().1
+                } else if let Some((_, body)) = constructor.as_ref() {
+                    body.get_pos().1
+                } else if let Some(property) = properties.last() {
+                    property.1.get_pos().1
+                } else {
+                    name.pos_end
+                },
+            ),
             Node::ClassInitNode {
                 name,
                 constructor_params,
@@ -484,6 +583,330 @@ impl Node {
     }
 }
 
+/*
+* Name, arguments, body and return type of a typed class method
+*/
+pub type TypedMethod = (Token, Vec<(Token, Type)>, TypedNode, Type);
+
+/*
+* Node annotated with its resolved type and span, returned by the Type Checker
+*/
+#[derive(Debug, Clone)]
+pub struct TypedNode {
+    pub node: TypedKind,
+    pub typee: Type,
+    pub pos: (Position, Position),
+}
+
+impl TypedNode {
+    /*
+     * Creates a new TypedNode Struct
+     */
+    pub fn new(node: TypedKind, typee: Type, pos: (Position, Position)) -> TypedNode {
+        TypedNode { node, typee, pos }
+    }
+
+    /*
+     * Registers the records of the object types in the node and its children
+     */
+    pub fn register_records(&mut self, records: &mut HashMap<String, Record>) {
+        let types = RefCell::new(vec![]);
+        self.map_types(&|x| {
+            types.borrow_mut().push(x.clone());
+            x.clone()
+        });
+        for typee in types.into_inner() {
+            Record::register(&typee, records);
+        }
+    }
+
+    /*
+     * Replaces every type in the node and its children, annotations included
+     */
+    pub fn map_types(&mut self, f: &dyn Fn(&Type) -> Type) {
+        let map_args = |args: &mut Vec<(Token, Type)>| {
+            for (_, typee) in args {
+                *typee = f(typee);
+            }
+        };
+
+        match &mut self.node {
+            TypedKind::Statements { statements } => {
+                for statement in statements {
+                    statement.map_types(f);
+                }
+            }
+            TypedKind::WhileNode {
+                condition_node,
+                body_node,
+                ..
+            } => {
+                condition_node.map_types(f);
+                body_node.map_types(f);
+            }
+            TypedKind::VarReassignNode { value, .. }
+            | TypedKind::VarAssignNode { value, .. }
+            | TypedKind::UnaryNode { node: value, .. }
+            | TypedKind::ObjectPropAccess { object: value, .. } => value.map_types(f),
+            TypedKind::IfNode { cases, else_case } => {
+                for (condition, body) in cases {
+                    condition.map_types(f);
+                    body.map_types(f);
+                }
+                if let Some(else_case) = else_case.as_mut() {
+                    else_case.map_types(f);
+                }
+            }
+            TypedKind::FunDef {
+                body_node,
+                arg_tokens,
+                return_type,
+                ..
+            } => {
+                map_args(arg_tokens);
+                *return_type = f(return_type);
+                body_node.map_types(f);
+            }
+            TypedKind::ForNode {
+                start_value,
+                end_value,
+                body_node,
+                step_value_node,
+                ..
+            } => {
+                start_value.map_types(f);
+                end_value.map_types(f);
+                step_value_node.map_types(f);
+                body_node.map_types(f);
+            }
+            TypedKind::ForInNode {
+                iterable_node,
+                body_node,
+                ..
+            } => {
+                iterable_node.map_types(f);
+                body_node.map_types(f);
+            }
+            TypedKind::CallNode { node_to_call, args } => {
+                node_to_call.map_types(f);
+                for arg in args {
+                    arg.map_types(f);
+                }
+            }
+            TypedKind::BinaryNode { left, right, .. }
+            | TypedKind::ArrayAcess {
+                array: left,
+                index: right,
+            }
+            | TypedKind::ObjectPropEdit {
+                object: left,
+                new_val: right,
+                ..
+            } => {
+                left.map_types(f);
+                right.map_types(f);
+            }
+            TypedKind::ArrayNode {
+                element_nodes: nodes,
+            }
+            | TypedKind::ClassInitNode {
+                constructor_params: nodes,
+                ..
+            } => {
+                for node in nodes {
+                    node.map_types(f);
+                }
+            }
+            TypedKind::ArrayEdit {
+                array,
+                index,
+                new_val,
+                ..
+            } => {
+                array.map_types(f);
+                index.map_types(f);
+                new_val.map_types(f);
+            }
+            TypedKind::ReturnNode { value } => {
+                if let Some(value) = value.as_mut() {
+                    value.map_types(f);
+                }
+            }
+            TypedKind::ObjectDefNode { properties } => {
+                for (_, value) in properties {
+                    value.map_types(f);
+                }
+            }
+            TypedKind::ClassDefNode {
+                constructor,
+                properties,
+                methods,
+                ..
+            } => {
+                for (_, value) in properties {
+                    value.map_types(f);
+                }
+                if let Some((arg_tokens, body)) = constructor.as_mut() {
+                    map_args(arg_tokens);
+                    body.map_types(f);
+                }
+                for (_, arg_tokens, body, return_type) in methods {
+                    map_args(arg_tokens);
+                    *return_type = f(return_type);
+                    body.map_types(f);
+                }
+            }
+            TypedKind::ExternNode {
+                arg_tokens,
+                return_type,
+                ..
+            } => {
+                for typee in arg_tokens {
+                    *typee = f(typee);
+                }
+                *return_type = f(return_type);
+            }
+            TypedKind::VarAccessNode { .. }
+            | TypedKind::StringNode { .. }
+            | TypedKind::NumberNode { .. }
+            | TypedKind::CharNode { .. }
+            | TypedKind::BooleanNode { .. }
+            | TypedKind::BreakNode { .. }
+            | TypedKind::ContinueNode { .. } => (),
+        }
+        self.typee = f(&self.typee);
+    }
+}
+
+/*
+* Enum of the kinds of TypedNode, mirroring the Node they were checked from
+*/
+#[derive(Debug, Clone)]
+pub enum TypedKind {
+    WhileNode {
+        condition_node: Box<TypedNode>,
+        body_node: Box<TypedNode>,
+        label: Option<Token>,
+    },
+    VarReassignNode {
+        name: Token,
+        typee: Token,
+        value: Box<TypedNode>,
+    },
+    VarAssignNode {
+        name: Token,
+        value: Box<TypedNode>,
+        reassignable: bool,
+    },
+    VarAccessNode {
+        token: Token,
+    },
+    UnaryNode {
+        node: Box<TypedNode>,
+        op_token: Token,
+    },
+    StringNode {
+        token: Token,
+    },
+    NumberNode {
+        token: Token,
+    },
+    IfNode {
+        cases: Vec<(TypedNode, TypedNode)>,
+        else_case: Box<Option<TypedNode>>,
+    },
+    FunDef {
+        name: Option<Token>,
+        body_node: Box<TypedNode>,
+        arg_tokens: Vec<(Token, Type)>,
+        return_type: Type,
+        captures: Vec<String>,
+    },
+    ForNode {
+        var_name_token: Token,
+        start_value: Box<TypedNode>,
+        end_value: Box<TypedNode>,
+        body_node: Box<TypedNode>,
+        step_value_node: Box<TypedNode>,
+        inclusive: bool,
+        label: Option<Token>,
+    },
+    ForInNode {
+        var_name_token: Token,
+        iterable_node: Box<TypedNode>,
+        body_node: Box<TypedNode>,
+        label: Option<Token>,
+    },
+    CharNode {
+        token: Token,
+    },
+    CallNode {
+        node_to_call: Box<TypedNode>,
+        args: Vec<TypedNode>,
+    },
+    BooleanNode {
+        token: Token,
+    },
+    BinaryNode {
+        left: Box<TypedNode>,
+        right: Box<TypedNode>,
+        op_token: Token,
+    },
+    ArrayNode {
+        element_nodes: Vec<TypedNode>,
+    },
+    ArrayAcess {
+        array: Box<TypedNode>,
+        index: Box<TypedNode>,
+    },
+    ArrayEdit {
+        array: Box<TypedNode>,
+        index: Box<TypedNode>,
+        typee: Token,
+        new_val: Box<TypedNode>,
+    },
+    Statements {
+        statements: Vec<TypedNode>,
+    },
+    ReturnNode {
+        value: Box<Option<TypedNode>>,
+    },
+    BreakNode {
+        label: Option<Token>,
+    },
+    ContinueNode {
+        label: Option<Token>,
+    },
+    ObjectDefNode {
+        properties: Vec<(Token, TypedNode)>,
+    },
+    ObjectPropAccess {
+        object: Box<TypedNode>,
+        property: Token,
+    },
+    ObjectPropEdit {
+        object: Box<TypedNode>,
+        property: Token,
+        new_val: Box<TypedNode>,
+    },
+    ClassDefNode {
+        name: Token,
+        constructor: Box<Option<(Vec<(Token, Type)>, TypedNode)>>,
+        properties: Vec<(Token, TypedNode)>,
+        methods: Vec<TypedMethod>,
+    },
+    ClassInitNode {
+        name: Token,
+        constructor_params: Vec<TypedNode>,
+    },
+    ExternNode {
+        name: Token,
+        arg_tokens: Vec<Type>,
+        return_type: Type,
+        var_args: bool,
+    },
+}
+
 pub fn try_any_to_basic(k: AnyTypeEnum) -> BasicTypeEnum {
     match k {
         AnyTypeEnum::ArrayType(x) => x.into(),
@@ -524,9 +947,182 @@ pub enum Type {
     Char,
     String,
     Void,
+    Any,
     Function(Vec<Type>, Box<Type>),
-    Array(Box<Type>, Token),
+    Array(Box<Type>),
+    Object(Vec<(String, Type)>),
     Custom(&'static str),
+    Var(usize),
+}
+
+static TYPE_VARS: AtomicUsize = AtomicUsize::new(0);
+
+impl Type {
+    /*
+     * Creates a type variable for the type checker to infer
+     */
+    pub fn fresh() -> Type {
+        Type::Var(TYPE_VARS.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /*
+     * The type variables in the type, in the order they appear
+     */
+    pub fn vars(&self) -> Vec<usize> {
+        match self {
+            Type::Var(id) => vec![*id],
+            Type::Function(params, ret) => {
+                let mut vars = params.iter().flat_map(|x| x.vars()).collect::<Vec<_>>();
+                vars.extend(ret.vars());
+                vars
+            }
+            Type::Array(typee) => typee.vars(),
+            Type::Object(properties) => properties.iter().flat_map(|x| x.1.vars()).collect(),
+            _ => vec![],
+        }
+    }
+
+    /*
+     * Replaces the type variables that are in the map
+     */
+    pub fn instantiate(&self, vars: &HashMap<usize, Type>) -> Type {
+        match self {
+            Type::Var(id) => vars.get(id).cloned().unwrap_or_else(|| self.clone()),
+            Type::Function(params, ret) => Type::Function(
+                params.iter().map(|x| x.instantiate(vars)).collect(),
+                Box::new(ret.instantiate(vars)),
+            ),
+            Type::Array(typee) => Type::Array(Box::new(typee.instantiate(vars))),
+            Type::Object(properties) => Type::Object(
+                properties
+                    .iter()
+                    .map(|(name, typee)| (name.clone(), typee.instantiate(vars)))
+                    .collect(),
+            ),
+            _ => self.clone(),
+        }
+    }
+
+    /*
+     * Maps the type variables of this type to the parts of the other type in
+     * the same place, the other type being an instance of this one
+     */
+    pub fn bind(&self, instance: &Type, vars: &mut HashMap<usize, Type>) {
+        match (self, instance) {
+            (Type::Var(id), _) => {
+                vars.entry(*id).or_insert_with(|| instance.clone());
+            }
+            (Type::Function(params, ret), Type::Function(other_params, other_ret)) => {
+                for (param, other) in params.iter().zip(other_params.iter()) {
+                    param.bind(other, vars);
+                }
+                ret.bind(other_ret, vars);
+            }
+            (Type::Array(typee), Type::Array(other)) => typee.bind(other, vars),
+            (Type::Object(properties), Type::Object(other)) => {
+                for (property, other) in properties.iter().zip(other.iter()) {
+                    property.1.bind(&other.1, vars);
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/*
+* Record type, the named fields of a object or class in the order they're laid out
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    pub name: String,
+    pub fields: Vec<(String, Type)>,
+}
+
+impl Record {
+    pub fn new(name: String, fields: Vec<(String, Type)>) -> Self {
+        Record { name, fields }
+    }
+
+    /*
+     * Objects with the same fields, in the same order, share one record named after their type
+     */
+    pub fn object(fields: &[(String, Type)]) -> Self {
+        Record::new(Type::Object(fields.to_vec()).to_string(), fields.to_vec())
+    }
+
+    /*
+     * Registers the record of every object type in the type that doesn't have
+     * one yet, objects with types still to be inferred get theirs once they are
+     */
+    pub fn register(typee: &Type, records: &mut HashMap<String, Record>) {
+        match typee {
+            Type::Object(fields) => {
+                for (_, field) in fields {
+                    Record::register(field, records);
+                }
+                if typee.vars().is_empty() {
+                    records
+                        .entry(typee.to_string())
+                        .or_insert_with(|| Record::object(fields));
+                }
+            }
+            Type::Function(params, ret) => {
+                for param in params {
+                    Record::register(param, records);
+                }
+                Record::register(ret, records);
+            }
+            Type::Array(typee) => Record::register(typee, records),
+            _ => (),
+        }
+    }
+
+    /*
+     * Position and type of a field
+     */
+    pub fn field(&self, name: &str) -> Option<(u32, &Type)> {
+        self.fields
+            .iter()
+            .enumerate()
+            .find(|(_, field)| field.0 == name)
+            .map(|(i, field)| (i as u32, &field.1))
+    }
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::Int => write!(f, "int"),
+            Type::Float => write!(f, "float"),
+            Type::Boolean => write!(f, "boolean"),
+            Type::Char => write!(f, "char"),
+            Type::String => write!(f, "string"),
+            Type::Void => write!(f, "void"),
+            Type::Any => write!(f, "any"),
+            Type::Function(params, ret) => write!(
+                f,
+                "fun({}): {}",
+                params
+                    .iter()
+                    .map(|x| x.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", "),
+                ret
+            ),
+            Type::Array(typee) => write!(f, "[{}]", typee),
+            Type::Object(properties) => write!(
+                f,
+                "{{ {} }}",
+                properties
+                    .iter()
+                    .map(|(name, typee)| format!("{}: {}", name, typee))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            Type::Custom(name) => write!(f, "{}", name),
+            Type::Var(id) => write!(f, "T{}", id),
+        }
+    }
 }
 
 impl<'ctx> Type {
@@ -547,23 +1143,19 @@ impl<'ctx> Type {
                 false,
             )
             .into(),
-            Type::Array(typee, size) => {
-                let size = size.value.into_int() as u32;
-                match typee.to_llvm_type(ctx) {
-                    AnyTypeEnum::ArrayType(x) => x.array_type(size),
-                    AnyTypeEnum::FloatType(x) => x.array_type(size),
-                    AnyTypeEnum::FunctionType(x) => {
-                        x.ptr_type(AddressSpace::Generic).array_type(size)
-                    }
-                    AnyTypeEnum::IntType(x) => x.array_type(size),
-                    AnyTypeEnum::PointerType(x) => x.array_type(size),
-                    AnyTypeEnum::StructType(x) => x.array_type(size),
-                    AnyTypeEnum::VectorType(x) => x.array_type(size),
-                    AnyTypeEnum::VoidType(_) => panic!(),
-                }
-                .into()
-            }
+            Type::Object(properties) => ctx
+                .struct_type(
+                    &properties
+                        .iter()
+                        .map(|x| try_any_to_basic(x.1.to_llvm_type(ctx)))
+                        .collect::<Vec<BasicTypeEnum>>()[..],
+                    false,
+                )
+                .into(),
             Type::Custom(_) => panic!("Custom types aren't supported yet!"),
+            Type::Any => panic!("'any' should be lowered by the compiler!"),
+            Type::Array(_) => panic!("Arrays should be lowered by the compiler!"),
+            Type::Var(_) => panic!("Type variables should be inferred before compiling!"),
         }
     }
 }
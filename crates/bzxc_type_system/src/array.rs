@@ -0,0 +1,171 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+use bzxc_shared::{Error, Node, Position, Token, Type, TypedKind, TypedNode};
+
+use crate::TypeChecker;
+
+impl TypeChecker {
+    pub(crate) fn array_decl(
+        &mut self,
+        element_nodes: Vec<Node>,
+        pos: (Position, Position),
+    ) -> Result<TypedNode, Error> {
+        let mut typee = Type::fresh();
+        let mut elements = vec![];
+        for (i, element) in element_nodes.iter().enumerate() {
+            let element = self.check(element)?;
+            if i == 0 {
+                typee = element.typee.clone();
+            } else {
+                self.unify(&typee, &element.typee, element.pos)?;
+            }
+            elements.push(element);
+        }
+
+        Ok(TypedNode::new(
+            TypedKind::ArrayNode {
+                element_nodes: elements,
+            },
+            Type::Array(Box::new(typee)),
+            pos,
+        ))
+    }
+
+    pub(crate) fn array_access(
+        &mut self,
+        array: Node,
+        index: Node,
+        pos: (Position, Position),
+    ) -> Result<TypedNode, Error> {
+        let array = self.check(&array)?;
+        let index = self.check(&index)?;
+        self.unify(&Type::Int, &index.typee, index.pos)?;
+
+        let typee = self.array_element(&array)?;
+
+        Ok(TypedNode::new(
+            TypedKind::ArrayAcess {
+                array: Box::new(array),
+                index: Box::new(index),
+            },
+            typee,
+            pos,
+        ))
+    }
+
+    pub(crate) fn array_edit(
+        &mut self,
+        array: Node,
+        index: Node,
+        typee: Token,
+        new_val: Node,
+        pos: (Position, Position),
+    ) -> Result<TypedNode, Error> {
+        let array = self.check(&array)?;
+        let index = self.check(&index)?;
+        self.unify(&Type::Int, &index.typee, index.pos)?;
+        let element = self.array_element(&array)?;
+
+        let new_val = self.check(&new_val)?;
+        self.value(&new_val, String::from("Can't assign a 'void' value"))?;
+        self.unify(&element, &new_val.typee, new_val.pos)?;
+        self.compound(&typee, &element, pos)?;
+
+        Ok(TypedNode::new(
+            TypedKind::ArrayEdit {
+                array: Box::new(array),
+                index: Box::new(index),
+                typee,
+                new_val: Box::new(new_val),
+            },
+            element,
+            pos,
+        ))
+    }
+
+    fn array_element(&self, array: &TypedNode) -> Result<Type, Error> {
+        match self.shallow(&array.typee) {
+            Type::Array(element) => Ok(*element),
+            Type::Var(_) => Err(self.error(
+                array.pos,
+                String::from("Can't infer the type of the array, consider annotating it"),
+            )),
+            _ => Err(self.error(
+                array.pos,
+                format!("Expected a array but found '{}'", self.prune(&array.typee)),
+            )),
+        }
+    }
+
+    /*
+     * Calls one of the methods every array has, like `arr.push(1)`
+     */
+    pub(crate) fn array_method(
+        &mut self,
+        array: TypedNode,
+        property: Token,
+        args: Vec<Node>,
+        pos: (Position, Position),
+    ) -> Result<TypedNode, Error> {
+        let element = self.array_element(&array)?;
+        let name = property.value.into_string();
+        let (params, ret) = match name.as_str() {
+            "len" => (vec![], Type::Int),
+            "push" => (vec![element.clone()], Type::Void),
+            "pop" => (vec![], element.clone()),
+            "insert" => (vec![Type::Int, element.clone()], Type::Void),
+            "remove" => (vec![Type::Int], element.clone()),
+            "slice" => (vec![Type::Int, Type::Int], array.typee.clone()),
+            _ => {
+                return Err(self.error(pos, format!("Arrays don't have a method named '{}'", name)))
+            }
+        };
+
+        if args.len() != params.len() {
+            return Err(self.error(
+                pos,
+                format!(
+                    "Expected {} argument(s) but found {}",
+                    params.len(),
+                    args.len()
+                ),
+            ));
+        }
+
+        let mut typed_args = vec![];
+        for (arg, param) in args.iter().zip(params.iter()) {
+            let arg = self.check(arg)?;
+            self.unify(param, &arg.typee, arg.pos)?;
+            typed_args.push(arg);
+        }
+
+        let method_pos = (array.pos.0, property.pos_end);
+        let method = TypedNode::new(
+            TypedKind::ObjectPropAccess {
+                object: Box::new(array),
+                property,
+            },
+            Type::Function(params, Box::new(ret.clone())),
+            method_pos,
+        );
+        Ok(TypedNode::new(
+            TypedKind::CallNode {
+                node_to_call: Box::new(method),
+                args: typed_args,
+            },
+            ret,
+            pos,
+        ))
+    }
+}
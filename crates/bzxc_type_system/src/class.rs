@@ -0,0 +1,191 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+use std::collections::HashMap;
+
+use bzxc_shared::{
+    to_static_str, DynType, Error, Method, Node, Position, Record, Token, Tokens, Type, TypedKind,
+    TypedNode,
+};
+
+use crate::{Class, TypeChecker};
+
+impl TypeChecker {
+    pub(crate) fn class_decl(
+        &mut self,
+        constructor: Option<(Vec<(Token, Type)>, Node)>,
+        properties: Vec<(Token, Node)>,
+        name: Token,
+        methods: Vec<Method>,
+        pos: (Position, Position),
+    ) -> Result<TypedNode, Error> {
+        let class_name = name.value.into_string();
+        if self.classes.contains_key(&class_name) {
+            return Err(self.error(pos, format!("Class '{}' already defined", class_name)));
+        }
+
+        let mut class = Class {
+            record: Record::new(class_name.clone(), vec![]),
+            methods: HashMap::new(),
+            constructor: vec![],
+        };
+        self.classes.insert(class_name.clone(), class.clone());
+
+        /* Properties are initialized in a function of their own by the compiler */
+        self.push_function(false);
+        let result = self.class_properties(properties);
+        self.pop_function();
+        let typed_properties = result?;
+        class.record.fields = typed_properties
+            .iter()
+            .map(|(name, value)| (name.value.into_string(), value.typee.clone()))
+            .collect();
+
+        for (name, arg_tokens, _, return_type) in &methods {
+            let method_name = name.value.into_string();
+            if class.methods.contains_key(&method_name) {
+                return Err(self.error(
+                    (name.pos_start, name.pos_end),
+                    format!(
+                        "Method '{}' already defined on '{}'",
+                        method_name, class_name
+                    ),
+                ));
+            }
+            for (_, typee) in arg_tokens {
+                self.resolve(typee, pos)?;
+            }
+            self.resolve(return_type, pos)?;
+
+            class.methods.insert(
+                method_name,
+                Type::Function(
+                    arg_tokens.iter().map(|x| x.1.clone()).collect(),
+                    Box::new(return_type.clone()),
+                ),
+            );
+        }
+
+        if let Some((arg_tokens, _)) = constructor.as_ref() {
+            for (_, typee) in arg_tokens {
+                self.resolve(typee, pos)?;
+            }
+            class.constructor = arg_tokens.iter().map(|x| x.1.clone()).collect();
+        }
+
+        self.classes.insert(class_name.clone(), class);
+
+        let soul = Type::Custom(to_static_str(class_name));
+        let typed_constructor = match constructor {
+            Some((arg_tokens, body)) => {
+                let body = self.method_body(&soul, arg_tokens.clone(), &body, Type::Void)?;
+                Some((arg_tokens, body))
+            }
+            None => None,
+        };
+
+        let mut typed_methods = vec![];
+        for (name, arg_tokens, body, return_type) in methods {
+            let body = self.method_body(&soul, arg_tokens.clone(), &body, return_type.clone())?;
+            typed_methods.push((name, arg_tokens, body, return_type));
+        }
+
+        Ok(TypedNode::new(
+            TypedKind::ClassDefNode {
+                name,
+                constructor: Box::new(typed_constructor),
+                properties: typed_properties,
+                methods: typed_methods,
+            },
+            Type::Void,
+            pos,
+        ))
+    }
+
+    pub(crate) fn class_init(
+        &mut self,
+        name: Token,
+        constructor_params: Vec<Node>,
+        pos: (Position, Position),
+    ) -> Result<TypedNode, Error> {
+        let class_name = name.value.into_string();
+        let class = self
+            .classes
+            .get(&class_name)
+            .cloned()
+            .ok_or_else(|| self.error(pos, format!("Class '{}' not found", class_name)))?;
+
+        if class.constructor.len() != constructor_params.len() {
+            return Err(self.error(
+                pos,
+                format!(
+                    "Expected {} argument(s) but found {}",
+                    class.constructor.len(),
+                    constructor_params.len()
+                ),
+            ));
+        }
+
+        let mut typed_params = vec![];
+        for (param, typee) in constructor_params.iter().zip(class.constructor.iter()) {
+            let param = self.check(param)?;
+            self.unify(typee, &param.typee, param.pos)?;
+            typed_params.push(param);
+        }
+
+        Ok(TypedNode::new(
+            TypedKind::ClassInitNode {
+                name,
+                constructor_params: typed_params,
+            },
+            Type::Custom(to_static_str(class_name)),
+            pos,
+        ))
+    }
+
+    fn class_properties(
+        &mut self,
+        properties: Vec<(Token, Node)>,
+    ) -> Result<Vec<(Token, TypedNode)>, Error> {
+        let mut typed_properties = vec![];
+        for (name, value) in properties {
+            let value = self.check(&value)?;
+            self.value(&value, String::from("Can't assign a 'void' value"))?;
+            typed_properties.push((name, value));
+        }
+
+        Ok(typed_properties)
+    }
+
+    fn method_body(
+        &mut self,
+        soul: &Type,
+        arg_tokens: Vec<(Token, Type)>,
+        body: &Node,
+        return_type: Type,
+    ) -> Result<TypedNode, Error> {
+        let pos = body.get_pos();
+        let mut args = vec![(
+            Token::new(
+                Tokens::Identifier,
+                pos.0,
+                pos.1,
+                DynType::String(String::from("soul")),
+            ),
+            soul.clone(),
+        )];
+        args.extend(arg_tokens);
+
+        Ok(self.fun_body(args, body, return_type, false, pos)?.0)
+    }
+}
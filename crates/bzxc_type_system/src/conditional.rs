@@ -0,0 +1,113 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+use bzxc_shared::{Error, Node, Position, Type, TypedKind, TypedNode};
+
+use crate::{always_returns, to_static_str, TypeChecker};
+
+impl TypeChecker {
+    /*
+     * A 'if' with a 'else' has the type its branches agree on, otherwise it has no value
+     */
+    pub(crate) fn if_decl(
+        &mut self,
+        cases: Vec<(Node, Node)>,
+        else_case: Option<Node>,
+        pos: (Position, Position),
+    ) -> Result<TypedNode, Error> {
+        let mut typed = vec![];
+        let mut branches = vec![];
+        for (condition, body) in &cases {
+            let condition = self.check(condition)?;
+            self.unify(&Type::Boolean, &condition.typee, condition.pos)?;
+            let typed_body = self.check_block(body)?;
+            if !always_returns(body) {
+                branches.push((typed_body.typee.clone(), typed_body.pos));
+            }
+            typed.push((condition, typed_body));
+        }
+
+        let else_case = match else_case {
+            Some(else_case) => {
+                let typed_else = self.check_block(&else_case)?;
+                if !always_returns(&else_case) {
+                    branches.push((typed_else.typee.clone(), typed_else.pos));
+                }
+                Some(typed_else)
+            }
+            None => None,
+        };
+
+        let mut typee = Type::Void;
+        if else_case.is_some() {
+            if let Some((first, first_pos)) = branches.first() {
+                typee = first.clone();
+                for (branch, branch_pos) in &branches[1..] {
+                    if !self.try_unify(&typee, branch, *branch_pos) {
+                        /* Fine as a statement, only an error if the value is used */
+                        let error = self
+                            .error(
+                                *branch_pos,
+                                format!(
+                                    "The branches of the 'if' have different types, '{}' and '{}'",
+                                    self.prune(&typee),
+                                    self.prune(branch)
+                                ),
+                            )
+                            .with_note(
+                                first_pos.0,
+                                first_pos.1,
+                                to_static_str(format!("This branch is '{}'", self.prune(&typee))),
+                            );
+                        self.valueless.push((pos, error));
+                        typee = Type::Void;
+                        break;
+                    }
+                }
+            }
+        }
+
+        Ok(TypedNode::new(
+            TypedKind::IfNode {
+                cases: typed,
+                else_case: Box::new(else_case),
+            },
+            typee,
+            pos,
+        ))
+    }
+
+    /*
+     * Errors if the node has no value, explaining why when it's a 'if'
+     */
+    pub(crate) fn value(&self, node: &TypedNode, description: String) -> Result<(), Error> {
+        if self.prune(&node.typee) != Type::Void {
+            return Ok(());
+        }
+
+        match &node.node {
+            TypedKind::Statements { statements } if !statements.is_empty() => {
+                self.value(statements.last().unwrap(), description)
+            }
+            TypedKind::IfNode { else_case, .. } if else_case.is_none() => Err(self.error(
+                node.pos,
+                String::from("'if' without 'else' used as a value"),
+            )),
+            TypedKind::IfNode { .. } => match self.valueless.iter().find(|x| x.0 == node.pos) {
+                Some((_, error)) => Err(error.clone()),
+                None => Err(self.error(node.pos, description)),
+            },
+            _ => Err(self.error(node.pos, description)),
+        }
+    }
+}
@@ -0,0 +1,342 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+use bzxc_shared::{Error, Node, Position, Token, Type, TypedKind, TypedNode};
+
+use crate::{Symbol, TypeChecker};
+
+impl TypeChecker {
+    /*
+     * Declares every top level function and extern first, so they can be
+     * called before their definition
+     */
+    pub(crate) fn hoist(&mut self, node: &Node) {
+        if let Node::Statements { statements } = node {
+            for statement in statements {
+                let (name, typee, var_args) = match statement {
+                    Node::FunDef {
+                        name: Some(name),
+                        arg_tokens,
+                        return_type,
+                        ..
+                    } => (
+                        name,
+                        Type::Function(
+                            arg_tokens.iter().map(|x| x.1.clone()).collect(),
+                            Box::new(return_type.clone()),
+                        ),
+                        false,
+                    ),
+                    Node::ExternNode {
+                        name,
+                        arg_tokens,
+                        return_type,
+                        var_args,
+                    } => (
+                        name,
+                        Type::Function(arg_tokens.clone(), Box::new(return_type.clone())),
+                        *var_args,
+                    ),
+                    _ => continue,
+                };
+
+                self.declare(
+                    name.value.into_string(),
+                    Symbol {
+                        typee,
+                        reassignable: false,
+                        var_args,
+                        local: false,
+                        scheme: vec![],
+                    },
+                );
+            }
+        }
+    }
+
+    pub(crate) fn fun_decl(
+        &mut self,
+        arg_tokens: Vec<(Token, Type)>,
+        body_node: Node,
+        name: Option<Token>,
+        return_type: Type,
+        pos: (Position, Position),
+    ) -> Result<TypedNode, Error> {
+        for (_, typee) in &arg_tokens {
+            self.resolve(typee, pos)?;
+        }
+        self.resolve(&return_type, pos)?;
+
+        let typee = Type::Function(
+            arg_tokens.iter().map(|x| x.1.clone()).collect(),
+            Box::new(return_type.clone()),
+        );
+
+        let symbol = Symbol {
+            typee: typee.clone(),
+            reassignable: false,
+            var_args: false,
+            local: false,
+            scheme: vec![],
+        };
+        if let Some(name) = &name {
+            self.declare(name.value.into_string(), symbol.clone());
+        }
+
+        /* Top level functions are hoisted, so they can run before what follows them */
+        let hoisted = match (&name, self.scopes.len() == 1 && self.functions.is_empty()) {
+            (Some(name), true) => Some(name.value.into_string()),
+            _ => self.hoisted.clone(),
+        };
+        let outer = std::mem::replace(&mut self.hoisted, hoisted);
+        let body = self.fun_body(
+            arg_tokens.clone(),
+            &body_node,
+            return_type.clone(),
+            true,
+            pos,
+        );
+        self.hoisted = outer;
+        let (body, captures) = body?;
+
+        /* A function capturing variables is a closure value held in a local */
+        match (&name, captures.is_empty()) {
+            (Some(name), false) => self.declare(
+                name.value.into_string(),
+                Symbol {
+                    local: true,
+                    ..symbol
+                },
+            ),
+            (Some(name), true) => self.generalize(&name.value.into_string()),
+            (None, _) => (),
+        }
+
+        Ok(TypedNode::new(
+            TypedKind::FunDef {
+                name,
+                body_node: Box::new(body),
+                arg_tokens,
+                return_type,
+                captures,
+            },
+            typee,
+            pos,
+        ))
+    }
+
+    /*
+     * Checks the body of a function, method or constructor in a scope of its own,
+     * returning the variables it captures if it's a closure
+     */
+    pub(crate) fn fun_body(
+        &mut self,
+        arg_tokens: Vec<(Token, Type)>,
+        body_node: &Node,
+        return_type: Type,
+        closure: bool,
+        pos: (Position, Position),
+    ) -> Result<(TypedNode, Vec<String>), Error> {
+        self.push_function(closure);
+        for (arg, typee) in arg_tokens {
+            self.declare(
+                arg.value.into_string(),
+                Symbol {
+                    typee,
+                    reassignable: false,
+                    var_args: false,
+                    local: true,
+                    scheme: vec![],
+                },
+            );
+        }
+
+        self.return_types.push(return_type.clone());
+        let result = self.check_body(body_node, &return_type, pos);
+        self.return_types.pop();
+        let captures = self.pop_function();
+
+        Ok((result?, captures))
+    }
+
+    pub(crate) fn fun_call(
+        &mut self,
+        node_to_call: Node,
+        args: Vec<Node>,
+        pos: (Position, Position),
+    ) -> Result<TypedNode, Error> {
+        let var_args = match &node_to_call {
+            Node::VarAccessNode { token } => self
+                .lookup(&token.value.into_string())
+                .map(|x| x.var_args)
+                .unwrap_or(false),
+            _ => false,
+        };
+
+        let func = match &node_to_call {
+            Node::ObjectPropAccess { object, property } => {
+                let object = self.check(object)?;
+                if let Type::Array(_) = self.shallow(&object.typee) {
+                    return self.array_method(object, property.clone(), args, pos);
+                }
+                self.obj_access(object, property.clone(), node_to_call.get_pos())?
+            }
+            _ => self.check(&node_to_call)?,
+        };
+        let (params, ret) = match self.shallow(&func.typee) {
+            Type::Function(params, ret) => (params, ret),
+            Type::Var(_) => {
+                let params: Vec<Type> = args.iter().map(|_| Type::fresh()).collect();
+                let ret = Box::new(Type::fresh());
+                self.unify(
+                    &func.typee,
+                    &Type::Function(params.clone(), ret.clone()),
+                    func.pos,
+                )?;
+                (params, ret)
+            }
+            _ => {
+                return Err(self.error(
+                    func.pos,
+                    format!(
+                        "Expected a function but found '{}'",
+                        self.prune(&func.typee)
+                    ),
+                ))
+            }
+        };
+
+        if args.len() < params.len() || (!var_args && args.len() > params.len()) {
+            return Err(self.error(
+                pos,
+                format!(
+                    "Expected {} argument(s) but found {}",
+                    params.len(),
+                    args.len()
+                ),
+            ));
+        }
+
+        let mut typed_args = vec![];
+        for (i, arg) in args.iter().enumerate() {
+            let arg = self.check(arg)?;
+            self.value(&arg, String::from("Can't pass a 'void' value"))?;
+            if let Some(param) = params.get(i) {
+                self.unify(param, &arg.typee, arg.pos)?;
+            } else if self.prune(&arg.typee) == Type::Any {
+                return Err(self.error(
+                    arg.pos,
+                    String::from("Can't pass a 'any' value as a variadic argument"),
+                ));
+            }
+            typed_args.push(arg);
+        }
+
+        Ok(TypedNode::new(
+            TypedKind::CallNode {
+                node_to_call: Box::new(func),
+                args: typed_args,
+            },
+            *ret,
+            pos,
+        ))
+    }
+
+    pub(crate) fn fun_extern(
+        &mut self,
+        name: Token,
+        arg_tokens: Vec<Type>,
+        return_type: Type,
+        var_args: bool,
+        pos: (Position, Position),
+    ) -> Result<TypedNode, Error> {
+        for typee in &arg_tokens {
+            self.resolve(typee, pos)?;
+        }
+        self.resolve(&return_type, pos)?;
+
+        let typee = Type::Function(arg_tokens.clone(), Box::new(return_type.clone()));
+        self.declare(
+            name.value.into_string(),
+            Symbol {
+                typee: typee.clone(),
+                reassignable: false,
+                var_args,
+                local: false,
+                scheme: vec![],
+            },
+        );
+
+        Ok(TypedNode::new(
+            TypedKind::ExternNode {
+                name,
+                arg_tokens,
+                return_type,
+                var_args,
+            },
+            typee,
+            pos,
+        ))
+    }
+
+    pub(crate) fn ret(
+        &mut self,
+        value: Option<Node>,
+        pos: (Position, Position),
+    ) -> Result<TypedNode, Error> {
+        let return_type = self.return_types.last().cloned().unwrap_or(Type::Int);
+
+        let value = match value {
+            Some(value) => {
+                let value = self.check(&value)?;
+                if self.prune(&return_type) == Type::Void {
+                    return Err(self.error(
+                        pos,
+                        String::from("Can't return a value from a 'void' function"),
+                    ));
+                }
+                self.value(
+                    &value,
+                    format!(
+                        "Expected a value of type '{}' to return",
+                        self.prune(&return_type)
+                    ),
+                )?;
+
+                self.unify(&return_type, &value.typee, pos)?;
+                Some(value)
+            }
+            None => {
+                self.unify(&return_type, &Type::Void, pos).map_err(|_| {
+                    self.error(
+                        pos,
+                        format!(
+                            "Expected a value of type '{}' to return",
+                            self.prune(&return_type)
+                        ),
+                    )
+                })?;
+                None
+            }
+        };
+
+        let typee = value.as_ref().map_or(Type::Void, |x| x.typee.clone());
+        Ok(TypedNode::new(
+            TypedKind::ReturnNode {
+                value: Box::new(value),
+            },
+            typee,
+            pos,
+        ))
+    }
+}
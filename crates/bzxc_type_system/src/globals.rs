@@ -0,0 +1,109 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+use std::collections::HashSet;
+
+use bzxc_shared::{Error, Node, Position};
+
+use crate::TypeChecker;
+
+/*
+* A use of a top level name, either in the body of a hoisted function or
+* directly in the top level statement at the index
+*/
+#[derive(Debug, Clone)]
+pub(crate) struct Use {
+    pub function: Option<String>,
+    pub statement: usize,
+    pub name: String,
+    pub pos: (Position, Position),
+}
+
+impl TypeChecker {
+    /*
+     * Notes where a global or top level function is used, for `check_order`
+     */
+    pub(crate) fn record_use(&mut self, name: &str, pos: (Position, Position)) {
+        self.uses.push(Use {
+            function: self.hoisted.clone(),
+            statement: self.statement,
+            name: name.to_string(),
+            pos,
+        });
+    }
+
+    /*
+     * Notes the statement initializing a global. A 'val' of a literal is a
+     * constant, so it can be read at any time
+     */
+    pub(crate) fn record_global(
+        &mut self,
+        name: &str,
+        value: &Node,
+        reassignable: bool,
+        pos: (Position, Position),
+    ) {
+        if let (
+            Node::NumberNode { .. }
+            | Node::StringNode { .. }
+            | Node::CharNode { .. }
+            | Node::BooleanNode { .. },
+            false,
+        ) = (value, reassignable)
+        {
+            return;
+        }
+
+        self.initialized
+            .entry(name.to_string())
+            .or_insert((self.statement, pos));
+    }
+
+    /*
+     * Hoisted functions can be called before the globals they read are
+     * initialized, so every use by a top level statement is followed through
+     * the functions it calls to the globals they read
+     */
+    pub(crate) fn check_order(&self) -> Result<(), Error> {
+        for direct in self.uses.iter().filter(|x| x.function.is_none()) {
+            let mut visited = HashSet::new();
+            let mut pending = vec![direct.name.clone()];
+            while let Some(name) = pending.pop() {
+                if !visited.insert(name.clone()) {
+                    continue;
+                }
+
+                if let Some((statement, pos)) = self.initialized.get(&name) {
+                    if *statement >= direct.statement && name != direct.name {
+                        return Err(self
+                            .error(
+                                direct.pos,
+                                format!(
+                                    "'{}' reads '{}' before it's initialized",
+                                    direct.name, name
+                                ),
+                            )
+                            .with_note(pos.0, pos.1, "Initialized here"));
+                    }
+                }
+
+                for inner in &self.uses {
+                    if inner.function.as_ref() == Some(&name) {
+                        pending.push(inner.name.clone());
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
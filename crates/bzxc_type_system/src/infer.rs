@@ -0,0 +1,476 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+use bzxc_shared::{to_static_str, Error, Position, Token, Type, TypedKind, TypedNode};
+
+use crate::{Symbol, TypeChecker};
+
+impl TypeChecker {
+    /*
+     * Replaces every inferred type variable in the type with what it was unified with
+     */
+    pub(crate) fn prune(&self, typee: &Type) -> Type {
+        match typee {
+            Type::Var(id) => match self.substitutions.get(id) {
+                Some(typee) => self.prune(typee),
+                None => typee.clone(),
+            },
+            Type::Function(params, ret) => Type::Function(
+                params.iter().map(|x| self.prune(x)).collect(),
+                Box::new(self.prune(ret)),
+            ),
+            Type::Array(typee) => Type::Array(Box::new(self.prune(typee))),
+            Type::Object(properties) => Type::Object(
+                properties
+                    .iter()
+                    .map(|(name, typee)| (name.clone(), self.prune(typee)))
+                    .collect(),
+            ),
+            _ => typee.clone(),
+        }
+    }
+
+    /*
+     * Unifies both the types, pointing to where the conflicting type was inferred on failure.
+     * 'any' is consistent with every type that can be boxed, the compiler inserts the casts
+     */
+    pub(crate) fn unify(
+        &mut self,
+        expected: &Type,
+        found: &Type,
+        pos: (Position, Position),
+    ) -> Result<(), Error> {
+        if self.unify_types(expected, found, pos, true) {
+            return Ok(());
+        }
+
+        let (expected_type, found_type) = (self.prune(expected), self.prune(found));
+        let description = match (&expected_type, &found_type) {
+            (Type::Any, typee) | (typee, Type::Any) if !is_boxable(typee) => format!(
+                "Can't box a '{}' into 'any', only 'int', 'float', 'boolean', 'char' and 'string' values can be",
+                typee
+            ),
+            _ => format!("Cannot unify '{}' with '{}'", expected_type, found_type),
+        };
+        let mut error = self.error(pos, description);
+        let mut noted = vec![pos];
+        for typee in [expected, found].iter() {
+            if let Some(site) = self.site(typee) {
+                if !noted.contains(&site) {
+                    error = error.with_note(
+                        site.0,
+                        site.1,
+                        to_static_str(format!("Inferred as '{}' here", self.prune(typee))),
+                    );
+                    noted.push(site);
+                }
+            }
+        }
+
+        Err(error)
+    }
+
+    /*
+     * Unifies both the types if possible, leaving the inferred types untouched if not
+     */
+    pub(crate) fn try_unify(
+        &mut self,
+        expected: &Type,
+        found: &Type,
+        pos: (Position, Position),
+    ) -> bool {
+        let (substitutions, sites) = (self.substitutions.clone(), self.sites.clone());
+        if self.unify_types(expected, found, pos, true) {
+            return true;
+        }
+
+        self.substitutions = substitutions;
+        self.sites = sites;
+        false
+    }
+
+    /*
+     * Resolves the type to 'int' or 'float', defaulting to 'int' if it's still unknown
+     */
+    pub(crate) fn numeric(&mut self, typee: &Type, pos: (Position, Position)) -> Option<Type> {
+        match self.prune(typee) {
+            Type::Var(id) => {
+                self.bind(id, Type::Int, pos);
+                Some(Type::Int)
+            }
+            typee @ Type::Int | typee @ Type::Float => Some(typee),
+            _ => None,
+        }
+    }
+
+    /*
+     * Quantifies the type variables of the function declared in the current scope
+     * that nothing else in scope refers to, so every use can pick its own types
+     */
+    pub(crate) fn generalize(&mut self, name: &str) {
+        let symbol = match self.scopes.last().and_then(|scope| scope.get(name)) {
+            Some(symbol) => symbol.clone(),
+            None => return,
+        };
+        let typee = self.prune(&symbol.typee);
+
+        let current = self.scopes.len() - 1;
+        let mut bound = vec![];
+        for (i, scope) in self.scopes.iter().enumerate() {
+            for (other, symbol) in scope {
+                if i != current || other != name {
+                    bound.extend(
+                        self.prune(&symbol.typee)
+                            .vars()
+                            .into_iter()
+                            .filter(|x| !symbol.scheme.contains(x)),
+                    );
+                }
+            }
+        }
+        for typee in &self.return_types {
+            bound.extend(self.prune(typee).vars());
+        }
+        for class in self.classes.values() {
+            let types = class
+                .record
+                .fields
+                .iter()
+                .map(|x| &x.1)
+                .chain(class.methods.values())
+                .chain(class.constructor.iter());
+            for typee in types {
+                bound.extend(self.prune(typee).vars());
+            }
+        }
+
+        let mut scheme = vec![];
+        for id in typee.vars() {
+            if !bound.contains(&id) && !scheme.contains(&id) {
+                scheme.push(id);
+            }
+        }
+        self.quantified.extend(scheme.iter().cloned());
+        self.declare(
+            name.to_string(),
+            Symbol {
+                typee,
+                /* Generic functions are compiled for each use instead of being held in a local */
+                local: symbol.local && scheme.is_empty(),
+                scheme,
+                ..symbol
+            },
+        );
+    }
+
+    /*
+     * The type of a use of the symbol, with fresh type variables for the quantified ones
+     */
+    pub(crate) fn instantiate(&self, symbol: &Symbol) -> Type {
+        let vars = symbol
+            .scheme
+            .iter()
+            .map(|id| (*id, Type::fresh()))
+            .collect();
+        self.prune(&symbol.typee).instantiate(&vars)
+    }
+
+    fn bind(&mut self, id: usize, typee: Type, pos: (Position, Position)) {
+        self.substitutions.insert(id, typee);
+        self.sites.insert(id, pos);
+    }
+
+    fn unify_types(
+        &mut self,
+        left: &Type,
+        right: &Type,
+        pos: (Position, Position),
+        boundary: bool,
+    ) -> bool {
+        let left = self.shallow(left);
+        let right = self.shallow(right);
+
+        match (&left, &right) {
+            (Type::Var(l), Type::Var(r)) if l == r => true,
+            (Type::Var(id), typee) | (typee, Type::Var(id)) => {
+                if self.occurs(*id, typee) {
+                    return false;
+                }
+                self.bind(*id, typee.clone(), pos);
+                true
+            }
+            (Type::Any, typee) | (typee, Type::Any) if boundary => is_boxable(typee),
+            (Type::Function(left_params, left_ret), Type::Function(right_params, right_ret)) => {
+                left_params.len() == right_params.len()
+                    && left_params
+                        .iter()
+                        .zip(right_params.iter())
+                        .all(|(l, r)| self.unify_types(l, r, pos, false))
+                    && self.unify_types(left_ret, right_ret, pos, false)
+            }
+            (Type::Array(left), Type::Array(right)) => self.unify_types(left, right, pos, false),
+            (Type::Object(left), Type::Object(right)) => {
+                left.len() == right.len()
+                    && left
+                        .iter()
+                        .zip(right.iter())
+                        .all(|(l, r)| l.0 == r.0 && self.unify_types(&l.1, &r.1, pos, false))
+            }
+            _ => left == right,
+        }
+    }
+
+    /*
+     * Follows the type variable until it isn't bound to another one
+     */
+    pub(crate) fn shallow(&self, typee: &Type) -> Type {
+        match typee {
+            Type::Var(id) => match self.substitutions.get(id) {
+                Some(typee) => self.shallow(typee),
+                None => typee.clone(),
+            },
+            _ => typee.clone(),
+        }
+    }
+
+    fn occurs(&self, id: usize, typee: &Type) -> bool {
+        match self.shallow(typee) {
+            Type::Var(other) => other == id,
+            Type::Function(params, ret) => {
+                params.iter().any(|x| self.occurs(id, x)) || self.occurs(id, &ret)
+            }
+            Type::Array(typee) => self.occurs(id, &typee),
+            Type::Object(properties) => properties.iter().any(|x| self.occurs(id, &x.1)),
+            _ => false,
+        }
+    }
+
+    /*
+     * Where the first type variable in the chain got its type
+     */
+    fn site(&self, typee: &Type) -> Option<(Position, Position)> {
+        match typee {
+            Type::Var(id) => self
+                .sites
+                .get(id)
+                .cloned()
+                .or_else(|| self.substitutions.get(id).and_then(|x| self.site(x))),
+            _ => None,
+        }
+    }
+
+    /*
+     * Writes the inferred types back into the node and the annotations in it
+     */
+    pub(crate) fn substitute(&self, node: &mut TypedNode) -> Result<(), Error> {
+        let pos = node.pos;
+        match &mut node.node {
+            TypedKind::Statements { statements } => {
+                for statement in statements {
+                    self.substitute(statement)?;
+                }
+            }
+            TypedKind::WhileNode {
+                condition_node,
+                body_node,
+                ..
+            } => {
+                self.substitute(condition_node)?;
+                self.substitute(body_node)?;
+            }
+            TypedKind::VarReassignNode { value, .. } | TypedKind::VarAssignNode { value, .. } => {
+                self.substitute(value)?
+            }
+            TypedKind::UnaryNode { node, .. } => self.substitute(node)?,
+            TypedKind::IfNode { cases, else_case } => {
+                for (condition, body) in cases {
+                    self.substitute(condition)?;
+                    self.substitute(body)?;
+                }
+                if let Some(else_case) = else_case.as_mut() {
+                    self.substitute(else_case)?;
+                }
+            }
+            TypedKind::FunDef {
+                body_node,
+                arg_tokens,
+                return_type,
+                ..
+            } => {
+                self.substitute_args(arg_tokens)?;
+                *return_type = self.concrete(return_type, pos, "the return type")?;
+                self.substitute(body_node)?;
+            }
+            TypedKind::ForNode {
+                start_value,
+                end_value,
+                body_node,
+                step_value_node,
+                ..
+            } => {
+                self.substitute(start_value)?;
+                self.substitute(end_value)?;
+                self.substitute(step_value_node)?;
+                self.substitute(body_node)?;
+            }
+            TypedKind::ForInNode {
+                iterable_node,
+                body_node,
+                ..
+            } => {
+                self.substitute(iterable_node)?;
+                self.substitute(body_node)?;
+            }
+            TypedKind::CallNode { node_to_call, args } => {
+                self.substitute(node_to_call)?;
+                for arg in args {
+                    self.substitute(arg)?;
+                }
+            }
+            TypedKind::BinaryNode { left, right, .. } => {
+                self.substitute(left)?;
+                self.substitute(right)?;
+            }
+            TypedKind::ArrayNode { element_nodes } => {
+                for element in element_nodes {
+                    self.substitute(element)?;
+                }
+            }
+            TypedKind::ArrayAcess { array, index } => {
+                self.substitute(array)?;
+                self.substitute(index)?;
+            }
+            TypedKind::ArrayEdit {
+                array,
+                index,
+                new_val,
+                ..
+            } => {
+                self.substitute(array)?;
+                self.substitute(index)?;
+                self.substitute(new_val)?;
+            }
+            TypedKind::ReturnNode { value } => {
+                if let Some(value) = value.as_mut() {
+                    self.substitute(value)?;
+                }
+            }
+            TypedKind::ObjectDefNode { properties } => {
+                for (_, value) in properties {
+                    self.substitute(value)?;
+                }
+            }
+            TypedKind::ObjectPropAccess { object, .. } => self.substitute(object)?,
+            TypedKind::ObjectPropEdit {
+                object, new_val, ..
+            } => {
+                self.substitute(object)?;
+                self.substitute(new_val)?;
+            }
+            TypedKind::ClassDefNode {
+                constructor,
+                properties,
+                methods,
+                ..
+            } => {
+                for (_, value) in properties {
+                    self.substitute(value)?;
+                }
+                if let Some((arg_tokens, body)) = constructor.as_mut() {
+                    self.substitute_args(arg_tokens)?;
+                    self.substitute(body)?;
+                }
+                for (name, arg_tokens, body, return_type) in methods {
+                    self.substitute_args(arg_tokens)?;
+                    *return_type = self.concrete(
+                        return_type,
+                        (name.pos_start, name.pos_end),
+                        "the return type",
+                    )?;
+                    self.substitute(body)?;
+                }
+            }
+            TypedKind::ClassInitNode {
+                constructor_params, ..
+            } => {
+                for param in constructor_params {
+                    self.substitute(param)?;
+                }
+            }
+            TypedKind::VarAccessNode { .. }
+            | TypedKind::StringNode { .. }
+            | TypedKind::NumberNode { .. }
+            | TypedKind::CharNode { .. }
+            | TypedKind::BooleanNode { .. }
+            | TypedKind::BreakNode { .. }
+            | TypedKind::ContinueNode { .. }
+            | TypedKind::ExternNode { .. } => (),
+        }
+        node.typee = self.concrete(&node.typee, pos, "the expression")?;
+
+        Ok(())
+    }
+
+    fn substitute_args(&self, arg_tokens: &mut Vec<(Token, Type)>) -> Result<(), Error> {
+        for (arg, typee) in arg_tokens {
+            *typee = self.concrete(
+                typee,
+                (arg.pos_start, arg.pos_end),
+                &format!("'{}'", arg.value.into_string()),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /*
+     * Prunes the type, erroring if it couldn't be inferred completely
+     */
+    fn concrete(&self, typee: &Type, pos: (Position, Position), what: &str) -> Result<Type, Error> {
+        let typee = self.prune(typee);
+        if self.has_vars(&typee) {
+            return Err(self.error(
+                pos,
+                format!("Can't infer the type of {}, consider annotating it", what),
+            ));
+        }
+
+        Ok(typee)
+    }
+
+    /*
+     * Whether the type has variables left to infer, the quantified ones of
+     * generalized functions are resolved at each use instead
+     */
+    fn has_vars(&self, typee: &Type) -> bool {
+        match typee {
+            Type::Var(id) => !self.quantified.contains(id),
+            Type::Function(params, ret) => {
+                params.iter().any(|x| self.has_vars(x)) || self.has_vars(ret)
+            }
+            Type::Array(typee) => self.has_vars(typee),
+            Type::Object(properties) => properties.iter().any(|x| self.has_vars(&x.1)),
+            _ => false,
+        }
+    }
+}
+
+/*
+* Whether values of the type can be boxed into a 'any'
+*/
+pub(crate) fn is_boxable(typee: &Type) -> bool {
+    matches!(
+        typee,
+        Type::Int | Type::Float | Type::Boolean | Type::Char | Type::String | Type::Any
+    )
+}
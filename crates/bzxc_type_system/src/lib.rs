@@ -1,15 +1,445 @@
-use bzxc_shared::Node;
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
 
+mod array;
+mod class;
+mod conditional;
+mod function;
+mod globals;
+mod infer;
+mod literals;
+mod loops;
+mod object;
+mod operation;
+mod variable;
+
+use std::collections::{HashMap, HashSet};
+
+use globals::Use;
+
+use bzxc_shared::{to_static_str, Error, Node, Position, Record, Type, TypedKind, TypedNode};
+
+/*
+* A variable, function or extern known to the type checker
+*/
+#[derive(Debug, Clone)]
+pub(crate) struct Symbol {
+    pub typee: Type,
+    pub reassignable: bool,
+    pub var_args: bool,
+    pub local: bool,
+    /* Type variables each use replaces with fresh ones, for generalized functions */
+    pub scheme: Vec<usize>,
+}
+
+/*
+* Properties, methods and constructor parameters of a class
+*/
+#[derive(Debug, Clone)]
+pub(crate) struct Class {
+    pub record: Record,
+    pub methods: HashMap<String, Type>,
+    pub constructor: Vec<Type>,
+}
+
+/*
+* The body of a function being checked. Closures record the variables of the
+* enclosing functions they use, while methods can't capture any
+*/
+#[derive(Debug, Clone)]
+pub(crate) struct Frame {
+    pub scope: usize,
+    pub captures: Option<Vec<String>>,
+}
+
+/*
+* Resolves the type of every node and reports mismatches before code generation
+*/
 pub struct TypeChecker {
     pub node: Node,
+    /* Records of the classes and object types, for the compiler to lay them out */
+    pub records: HashMap<String, Record>,
+
+    scopes: Vec<HashMap<String, Symbol>>,
+    functions: Vec<Frame>,
+    loops: Vec<(Option<String>, usize)>,
+    classes: HashMap<String, Class>,
+    return_types: Vec<Type>,
+    substitutions: HashMap<usize, Type>,
+    sites: HashMap<usize, (Position, Position)>,
+    valueless: Vec<((Position, Position), Error)>,
+    statement: usize,
+    hoisted: Option<String>,
+    uses: Vec<Use>,
+    initialized: HashMap<String, (usize, (Position, Position))>,
+    quantified: HashSet<usize>,
 }
 
 impl TypeChecker {
     pub fn new(node: Node) -> Self {
-        TypeChecker { node }
+        let mut globals = HashMap::new();
+        globals.insert(
+            String::from("printf"),
+            Symbol {
+                typee: Type::Function(vec![Type::String], Box::new(Type::Int)),
+                reassignable: false,
+                var_args: true,
+                local: false,
+                scheme: vec![],
+            },
+        );
+
+        TypeChecker {
+            node,
+            records: HashMap::new(),
+            scopes: vec![globals],
+            functions: vec![],
+            loops: vec![],
+            classes: HashMap::new(),
+            return_types: vec![],
+            substitutions: HashMap::new(),
+            sites: HashMap::new(),
+            valueless: vec![],
+            statement: 0,
+            hoisted: None,
+            uses: vec![],
+            initialized: HashMap::new(),
+            quantified: HashSet::new(),
+        }
+    }
+
+    /*
+     * Type checks the whole program, which is compiled as the body of `main`,
+     * into a tree annotated with the inferred types
+     */
+    pub fn typed_node(&mut self) -> Result<TypedNode, Error> {
+        let node = self.node.clone();
+        self.hoist(&node);
+        self.return_types.push(Type::Int);
+        let mut typed = self.check_body(&node, &Type::Int, node.get_pos())?;
+        self.return_types.pop();
+        self.check_order()?;
+        self.substitute(&mut typed)?;
+
+        for class in self.classes.values() {
+            let fields = class
+                .record
+                .fields
+                .iter()
+                .map(|(name, typee)| (name.clone(), self.prune(typee)))
+                .collect();
+            self.records.insert(
+                class.record.name.clone(),
+                Record::new(class.record.name.clone(), fields),
+            );
+        }
+        typed.register_records(&mut self.records);
+
+        Ok(typed)
+    }
+
+    fn error(&self, pos: (Position, Position), description: String) -> Error {
+        Error::new("Type Error", pos.0, pos.1, to_static_str(description))
+    }
+
+    /*
+     * Errors if a annotation refers to a class that doesn't exist
+     */
+    fn resolve(&self, typee: &Type, pos: (Position, Position)) -> Result<(), Error> {
+        match typee {
+            Type::Custom(name) => {
+                if self.classes.contains_key(*name) {
+                    Ok(())
+                } else {
+                    Err(self.error(pos, format!("Unknown type '{}'", name)))
+                }
+            }
+            Type::Function(params, ret) => {
+                for param in params {
+                    self.resolve(param, pos)?;
+                }
+                self.resolve(ret, pos)
+            }
+            Type::Array(typee) => self.resolve(typee, pos),
+            Type::Object(properties) => {
+                for (_, typee) in properties {
+                    self.resolve(typee, pos)?;
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn push_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    fn pop_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    /*
+     * Opens the scope of a function body, only closures can use the locals outside of it
+     */
+    fn push_function(&mut self, closure: bool) {
+        self.push_scope();
+        self.functions.push(Frame {
+            scope: self.scopes.len() - 1,
+            captures: if closure { Some(vec![]) } else { None },
+        });
     }
 
-    pub fn typed_node(&self) -> Node {
-        self.node.clone()
+    fn pop_function(&mut self) -> Vec<String> {
+        self.pop_scope();
+        self.functions
+            .pop()
+            .and_then(|frame| frame.captures)
+            .unwrap_or_default()
+    }
+
+    fn declare(&mut self, name: String, symbol: Symbol) {
+        self.scopes.last_mut().unwrap().insert(name, symbol);
+    }
+
+    fn lookup(&self, name: &str) -> Option<&Symbol> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name))
+    }
+
+    /*
+     * Finds the symbol and the index of its scope
+     */
+    fn find(&self, name: &str) -> Option<(usize, Symbol)> {
+        self.scopes
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(i, scope)| scope.get(name).map(|symbol| (i, symbol.clone())))
+    }
+
+    /*
+     * Finds the symbol, capturing it into every closure between its scope and
+     * the current one. Errors if a method would have to capture it
+     */
+    fn visible(&mut self, name: &str, pos: (Position, Position)) -> Result<Symbol, Error> {
+        let (i, symbol) = self
+            .find(name)
+            .ok_or_else(|| self.error(pos, format!("Variable '{}' not found", name)))?;
+        if !symbol.local {
+            if i == 0 {
+                self.record_use(name, pos);
+            }
+            return Ok(symbol);
+        }
+
+        for j in (0..self.functions.len()).rev() {
+            if self.functions[j].scope <= i {
+                break;
+            }
+
+            match self.functions[j].captures.as_mut() {
+                Some(captures) => {
+                    if !captures.iter().any(|x| x == name) {
+                        captures.push(name.to_string());
+                    }
+                }
+                None => {
+                    return Err(self.error(
+                        pos,
+                        format!("Can't access '{}' from an enclosing function", name),
+                    ))
+                }
+            }
+        }
+
+        Ok(symbol)
+    }
+
+    /*
+     * Whether the variable belongs to a function enclosing the current one
+     */
+    fn captured(&self, name: &str) -> bool {
+        match (self.find(name), self.functions.last()) {
+            (Some((i, symbol)), Some(frame)) => symbol.local && i < frame.scope,
+            _ => false,
+        }
+    }
+
+    /*
+     * Checks a function body against its declared return type
+     */
+    fn check_body(
+        &mut self,
+        body: &Node,
+        return_type: &Type,
+        pos: (Position, Position),
+    ) -> Result<TypedNode, Error> {
+        let typed = self.check(body)?;
+        if self.prune(return_type) != Type::Void && !always_returns(body) {
+            /* An inferred return type can still turn out to be 'void' */
+            if !matches!(self.prune(return_type), Type::Var(_)) {
+                self.value(
+                    &typed,
+                    format!(
+                        "Expected a value of type '{}' to return",
+                        self.prune(return_type)
+                    ),
+                )?;
+            }
+            self.unify(return_type, &typed.typee, pos)?;
+        }
+
+        Ok(typed)
+    }
+
+    /*
+     * Checks the body of a 'if', 'else' or loop in a scope of its own
+     */
+    fn check_block(&mut self, node: &Node) -> Result<TypedNode, Error> {
+        self.push_scope();
+        let result = self.check(node);
+        self.pop_scope();
+
+        result
+    }
+
+    fn check(&mut self, node: &Node) -> Result<TypedNode, Error> {
+        let pos = node.get_pos();
+        match node.clone() {
+            Node::Statements { statements } => {
+                let top_level = self.scopes.len() == 1 && self.functions.is_empty();
+                let mut typed = vec![];
+                for (i, statement) in statements.iter().enumerate() {
+                    if top_level {
+                        self.statement = i;
+                    }
+                    typed.push(self.check(statement)?);
+                }
+
+                let typee = typed.last().map_or(Type::Int, |x| x.typee.clone());
+                Ok(TypedNode::new(
+                    TypedKind::Statements { statements: typed },
+                    typee,
+                    pos,
+                ))
+            }
+            Node::WhileNode {
+                condition_node,
+                body_node,
+                label,
+            } => self.while_loop(*condition_node, *body_node, label, pos),
+            Node::VarReassignNode { name, typee, value } => {
+                self.var_reassign(name, *value, typee, pos)
+            }
+            Node::VarAssignNode {
+                name,
+                value,
+                reassignable,
+            } => self.var_assign(name, *value, reassignable, pos),
+            Node::VarAccessNode { token } => self.var_access(token, pos),
+            Node::UnaryNode { node, op_token } => self.unary_op(*node, op_token, pos),
+            Node::StringNode { token } => self.string(token, pos),
+            Node::NumberNode { token } => self.num(token, pos),
+            Node::CharNode { token } => self.char(token, pos),
+            Node::BooleanNode { token } => self.boolean(token, pos),
+            Node::IfNode { cases, else_case } => self.if_decl(cases, *else_case, pos),
+            Node::FunDef {
+                name,
+                arg_tokens,
+                body_node,
+                return_type,
+            } => self.fun_decl(arg_tokens, *body_node, name, return_type, pos),
+            Node::ForNode {
+                var_name_token,
+                start_value,
+                end_value,
+                body_node,
+                step_value_node,
+                inclusive,
+                label,
+            } => self.for_loop(
+                var_name_token,
+                *start_value,
+                *end_value,
+                *body_node,
+                *step_value_node,
+                inclusive,
+                label,
+                pos,
+            ),
+            Node::ForInNode {
+                var_name_token,
+                iterable_node,
+                body_node,
+                label,
+            } => self.for_in(var_name_token, *iterable_node, *body_node, label, pos),
+            Node::CallNode { node_to_call, args } => self.fun_call(*node_to_call, args, pos),
+            Node::BinaryNode {
+                left,
+                right,
+                op_token,
+            } => self.binary_op(*left, op_token, *right, pos),
+            Node::ArrayNode { element_nodes, .. } => self.array_decl(element_nodes, pos),
+            Node::ArrayAcess { array, index } => self.array_access(*array, *index, pos),
+            Node::ArrayEdit {
+                array,
+                index,
+                typee,
+                new_val,
+            } => self.array_edit(*array, *index, typee, *new_val, pos),
+            Node::ReturnNode { value, .. } => self.ret(*value, pos),
+            Node::BreakNode { label, .. } => self.loop_control(true, label, pos),
+            Node::ContinueNode { label, .. } => self.loop_control(false, label, pos),
+            Node::ObjectDefNode { properties } => self.obj_decl(properties, pos),
+            Node::ObjectPropAccess { object, property } => self.obj_get(*object, property, pos),
+            Node::ObjectPropEdit {
+                object,
+                property,
+                new_val,
+            } => self.obj_edit(*object, property, *new_val, pos),
+            Node::ClassDefNode {
+                name,
+                constructor,
+                properties,
+                methods,
+            } => self.class_decl(*constructor, properties, name, methods, pos),
+            Node::ClassInitNode {
+                name,
+                constructor_params,
+            } => self.class_init(name, constructor_params, pos),
+            Node::ExternNode {
+                name,
+                arg_tokens,
+                return_type,
+                var_args,
+            } => self.fun_extern(name, arg_tokens, return_type, var_args, pos),
+        }
+    }
+}
+
+/*
+* Whether every path through the node ends in a 'return'
+*/
+pub(crate) fn always_returns(node: &Node) -> bool {
+    match node {
+        Node::ReturnNode { .. } => true,
+        Node::Statements { statements } => statements.iter().any(always_returns),
+        Node::IfNode { cases, else_case } => match else_case.as_ref() {
+            Some(else_case) => {
+                always_returns(else_case) && cases.iter().all(|(_, body)| always_returns(body))
+            }
+            None => false,
+        },
+        _ => false,
     }
 }
@@ -0,0 +1,60 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+use bzxc_shared::{DynType, Error, Position, Token, Type, TypedKind, TypedNode};
+
+use crate::TypeChecker;
+
+impl TypeChecker {
+    pub(crate) fn num(&self, token: Token, pos: (Position, Position)) -> Result<TypedNode, Error> {
+        let typee = if let DynType::Float(_) = token.value {
+            Type::Float
+        } else {
+            Type::Int
+        };
+
+        Ok(TypedNode::new(TypedKind::NumberNode { token }, typee, pos))
+    }
+
+    pub(crate) fn string(
+        &self,
+        token: Token,
+        pos: (Position, Position),
+    ) -> Result<TypedNode, Error> {
+        Ok(TypedNode::new(
+            TypedKind::StringNode { token },
+            Type::String,
+            pos,
+        ))
+    }
+
+    pub(crate) fn char(&self, token: Token, pos: (Position, Position)) -> Result<TypedNode, Error> {
+        Ok(TypedNode::new(
+            TypedKind::CharNode { token },
+            Type::Char,
+            pos,
+        ))
+    }
+
+    pub(crate) fn boolean(
+        &self,
+        token: Token,
+        pos: (Position, Position),
+    ) -> Result<TypedNode, Error> {
+        Ok(TypedNode::new(
+            TypedKind::BooleanNode { token },
+            Type::Boolean,
+            pos,
+        ))
+    }
+}
@@ -0,0 +1,251 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+use bzxc_shared::{DynType, Error, Node, Position, Token, Tokens, Type, TypedKind, TypedNode};
+
+use crate::{Symbol, TypeChecker};
+
+impl TypeChecker {
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn for_loop(
+        &mut self,
+        var_name_token: Token,
+        start_value: Node,
+        end_value: Node,
+        body_node: Node,
+        step_value_node: Option<Node>,
+        inclusive: bool,
+        label: Option<Token>,
+        pos: (Position, Position),
+    ) -> Result<TypedNode, Error> {
+        let start = self.check(&start_value)?;
+        if self.numeric(&start.typee, start.pos).is_none() {
+            return Err(self.error(
+                start.pos,
+                format!(
+                    "Expected 'int' or 'float' but found '{}'",
+                    self.prune(&start.typee)
+                ),
+            ));
+        }
+
+        let end = self.check(&end_value)?;
+        self.unify(&start.typee, &end.typee, end.pos)?;
+        let step = match step_value_node {
+            Some(step_value_node) => {
+                let step = self.check(&step_value_node)?;
+                self.unify(&start.typee, &step.typee, step.pos)?;
+                step
+            }
+            None => self.unit_step(&start.typee, end.pos),
+        };
+
+        self.push_scope();
+        self.declare(
+            var_name_token.value.into_string(),
+            Symbol {
+                typee: start.typee.clone(),
+                reassignable: true,
+                var_args: false,
+                local: true,
+                scheme: vec![],
+            },
+        );
+        let body = self.loop_body(&label, |checker| checker.check(&body_node));
+        self.pop_scope();
+
+        Ok(TypedNode::new(
+            TypedKind::ForNode {
+                var_name_token,
+                start_value: Box::new(start),
+                end_value: Box::new(end),
+                body_node: Box::new(body?),
+                step_value_node: Box::new(step),
+                inclusive,
+                label,
+            },
+            Type::Void,
+            pos,
+        ))
+    }
+
+    /*
+     * Step of a loop without one, 1 or 1.0 depending on the type of the variable
+     */
+    fn unit_step(&self, typee: &Type, pos: (Position, Position)) -> TypedNode {
+        let typee = self.prune(typee);
+        let (kind, one) = match typee {
+            Type::Float => (Tokens::Float, DynType::Float(1.0)),
+            _ => (Tokens::Int, DynType::Int(1)),
+        };
+
+        TypedNode::new(
+            TypedKind::NumberNode {
+                token: Token::new(kind, pos.0, pos.1, one),
+            },
+            typee,
+            pos,
+        )
+    }
+
+    pub(crate) fn for_in(
+        &mut self,
+        var_name_token: Token,
+        iterable_node: Node,
+        body_node: Node,
+        label: Option<Token>,
+        pos: (Position, Position),
+    ) -> Result<TypedNode, Error> {
+        let iterable = self.check(&iterable_node)?;
+        let element = self.element(&iterable.typee, iterable.pos)?;
+
+        self.push_scope();
+        self.declare(
+            var_name_token.value.into_string(),
+            Symbol {
+                typee: element,
+                reassignable: true,
+                var_args: false,
+                local: true,
+                scheme: vec![],
+            },
+        );
+        let body = self.loop_body(&label, |checker| checker.check(&body_node));
+        self.pop_scope();
+
+        Ok(TypedNode::new(
+            TypedKind::ForInNode {
+                var_name_token,
+                iterable_node: Box::new(iterable),
+                body_node: Box::new(body?),
+                label,
+            },
+            Type::Void,
+            pos,
+        ))
+    }
+
+    /*
+     * Finds the type of the items a 'for in' loop walks over. Classes are
+     * iterable when they have 'has_next(): bool' and 'next()' methods.
+     */
+    fn element(&mut self, typee: &Type, pos: (Position, Position)) -> Result<Type, Error> {
+        match self.shallow(typee) {
+            Type::Array(element) => Ok(*element),
+            Type::String => Ok(Type::Char),
+            Type::Custom(class_name) => {
+                let methods = &self.classes[class_name].methods;
+                match (methods.get("has_next"), methods.get("next")) {
+                    (
+                        Some(Type::Function(args, has_next)),
+                        Some(Type::Function(next_args, next)),
+                    ) if args.is_empty() && next_args.is_empty() => {
+                        let (has_next, next) = (*has_next.clone(), *next.clone());
+                        self.unify(&Type::Boolean, &has_next, pos)?;
+                        Ok(next)
+                    }
+                    _ => Err(self.error(
+                        pos,
+                        format!(
+                            "Class '{}' needs 'has_next()' and 'next()' methods to be iterated",
+                            class_name
+                        ),
+                    )),
+                }
+            }
+            Type::Var(_) => Err(self.error(
+                pos,
+                String::from("Can't infer the type of the iterable, consider annotating it"),
+            )),
+            typee => Err(self.error(pos, format!("Can't iterate over '{}'", self.prune(&typee)))),
+        }
+    }
+
+    pub(crate) fn while_loop(
+        &mut self,
+        condition_node: Node,
+        body_node: Node,
+        label: Option<Token>,
+        pos: (Position, Position),
+    ) -> Result<TypedNode, Error> {
+        let condition = self.check(&condition_node)?;
+        self.unify(&Type::Boolean, &condition.typee, condition.pos)?;
+        let body = self.loop_body(&label, |checker| checker.check_block(&body_node))?;
+
+        Ok(TypedNode::new(
+            TypedKind::WhileNode {
+                condition_node: Box::new(condition),
+                body_node: Box::new(body),
+                label,
+            },
+            Type::Void,
+            pos,
+        ))
+    }
+
+    /*
+     * Checks the body of a loop, which 'break' and 'continue' can refer to
+     */
+    fn loop_body(
+        &mut self,
+        label: &Option<Token>,
+        check: impl FnOnce(&mut Self) -> Result<TypedNode, Error>,
+    ) -> Result<TypedNode, Error> {
+        self.loops.push((
+            label.as_ref().map(|x| x.value.into_string()),
+            self.functions.len(),
+        ));
+        let body = check(self);
+        self.loops.pop();
+
+        body
+    }
+
+    /*
+     * Checks a 'break' or 'continue' refers to a loop of the current function
+     */
+    pub(crate) fn loop_control(
+        &mut self,
+        is_break: bool,
+        label: Option<Token>,
+        pos: (Position, Position),
+    ) -> Result<TypedNode, Error> {
+        let keyword = if is_break { "break" } else { "continue" };
+        let mut loops = self
+            .loops
+            .iter()
+            .rev()
+            .take_while(|x| x.1 == self.functions.len());
+
+        match &label {
+            Some(label) => {
+                let name = label.value.into_string();
+                if !loops.any(|x| x.0.as_ref() == Some(&name)) {
+                    return Err(self.error(pos, format!("Loop '{}' not found", name)));
+                }
+            }
+            None => {
+                if loops.next().is_none() {
+                    return Err(self.error(pos, format!("'{}' outside of a loop", keyword)));
+                }
+            }
+        }
+
+        let node = if is_break {
+            TypedKind::BreakNode { label }
+        } else {
+            TypedKind::ContinueNode { label }
+        };
+        Ok(TypedNode::new(node, Type::Void, pos))
+    }
+}
@@ -0,0 +1,146 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+use bzxc_shared::{Error, Node, Position, Record, Token, Type, TypedKind, TypedNode};
+
+use crate::TypeChecker;
+
+impl TypeChecker {
+    pub(crate) fn obj_decl(
+        &mut self,
+        properties: Vec<(Token, Node)>,
+        pos: (Position, Position),
+    ) -> Result<TypedNode, Error> {
+        let mut types = vec![];
+        let mut typed = vec![];
+        for (name, value) in properties {
+            let value = self.check(&value)?;
+            self.value(&value, String::from("Can't assign a 'void' value"))?;
+            types.push((name.value.into_string(), value.typee.clone()));
+            typed.push((name, value));
+        }
+
+        Ok(TypedNode::new(
+            TypedKind::ObjectDefNode { properties: typed },
+            Type::Object(types),
+            pos,
+        ))
+    }
+
+    pub(crate) fn obj_get(
+        &mut self,
+        object: Node,
+        property: Token,
+        pos: (Position, Position),
+    ) -> Result<TypedNode, Error> {
+        let object = self.check(&object)?;
+        self.obj_access(object, property, pos)
+    }
+
+    /*
+     * Property access on a object that's already checked
+     */
+    pub(crate) fn obj_access(
+        &mut self,
+        object: TypedNode,
+        property: Token,
+        pos: (Position, Position),
+    ) -> Result<TypedNode, Error> {
+        let typee = self.obj_prop(&object.typee, &property, pos, true)?;
+
+        Ok(TypedNode::new(
+            TypedKind::ObjectPropAccess {
+                object: Box::new(object),
+                property,
+            },
+            typee,
+            pos,
+        ))
+    }
+
+    pub(crate) fn obj_edit(
+        &mut self,
+        object: Node,
+        property: Token,
+        new_val: Node,
+        pos: (Position, Position),
+    ) -> Result<TypedNode, Error> {
+        let object = self.check(&object)?;
+        let prop = self.obj_prop(&object.typee, &property, pos, false)?;
+        let new_val = self.check(&new_val)?;
+        self.value(&new_val, String::from("Can't assign a 'void' value"))?;
+        self.unify(&prop, &new_val.typee, new_val.pos)?;
+
+        Ok(TypedNode::new(
+            TypedKind::ObjectPropEdit {
+                object: Box::new(object),
+                property,
+                new_val: Box::new(new_val),
+            },
+            prop,
+            pos,
+        ))
+    }
+
+    /*
+     * Type of a property (or a method, if allowed) on a object or class instance
+     */
+    fn obj_prop(
+        &mut self,
+        typee: &Type,
+        property: &Token,
+        pos: (Position, Position),
+        methods: bool,
+    ) -> Result<Type, Error> {
+        let name = property.value.into_string();
+        let found = match self.shallow(typee) {
+            Type::Object(properties) => {
+                let object = self.prune(&Type::Object(properties.clone()));
+                Record::register(&object, &mut self.records);
+                match self.records.get(&object.to_string()) {
+                    Some(record) => record.field(&name).map(|x| x.1.clone()),
+                    /* Its record waits until the types of the fields are inferred */
+                    None => properties.iter().find(|x| x.0 == name).map(|x| x.1.clone()),
+                }
+            }
+            Type::Custom(class_name) => self.classes.get(class_name).and_then(|class| {
+                class.record.field(&name).map(|x| x.1.clone()).or_else(|| {
+                    if methods {
+                        class.methods.get(&name).cloned()
+                    } else {
+                        None
+                    }
+                })
+            }),
+            Type::Var(_) => {
+                return Err(self.error(
+                    pos,
+                    String::from("Can't infer the type of the object, consider annotating it"),
+                ))
+            }
+            _ => {
+                return Err(self.error(
+                    pos,
+                    format!("Expected a object but found '{}'", self.prune(typee)),
+                ))
+            }
+        };
+
+        found.ok_or_else(|| {
+            self.error(
+                pos,
+                format!("Property '{}' not found on '{}'", name, self.prune(typee)),
+            )
+        })
+    }
+}
@@ -0,0 +1,162 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+use bzxc_shared::{DynType, Error, Node, Position, Token, Tokens, Type, TypedKind, TypedNode};
+
+use crate::TypeChecker;
+
+impl TypeChecker {
+    pub(crate) fn binary_op(
+        &mut self,
+        left: Node,
+        op_token: Token,
+        right: Node,
+        pos: (Position, Position),
+    ) -> Result<TypedNode, Error> {
+        let left = self.check(&left)?;
+        let right = self.check(&right)?;
+        self.unify(&left.typee, &right.typee, right.pos)?;
+
+        let logical = op_token.matches(Tokens::Keyword, DynType::String("and".to_string()))
+            || op_token.matches(Tokens::Keyword, DynType::String("or".to_string()));
+        if !logical && self.prune(&left.typee) == Type::Any && self.prune(&right.typee) == Type::Any
+        {
+            return Err(self.error(
+                pos,
+                String::from(
+                    "Can't operate on two 'any' values, annotate one of them with a type first",
+                ),
+            ));
+        }
+
+        /* A 'any' operand is casted to the type of the other one */
+        let left_type = if self.prune(&left.typee) == Type::Any {
+            right.typee.clone()
+        } else {
+            left.typee.clone()
+        };
+
+        let typee = match op_token.typee {
+            Tokens::DoubleEquals | Tokens::NotEquals => Ok(Type::Boolean),
+            Tokens::Plus
+            | Tokens::Minus
+            | Tokens::Multiply
+            | Tokens::Divide
+            | Tokens::Modulo
+            | Tokens::Power => self.numeric(&left_type, pos).ok_or_else(|| {
+                self.error(
+                    pos,
+                    format!("Arithmetic isn't supported on '{}'", self.prune(&left_type)),
+                )
+            }),
+            Tokens::BitwiseAnd | Tokens::BitwiseOr | Tokens::BitwiseXor
+                if self.prune(&left_type) == Type::Boolean =>
+            {
+                Ok(Type::Boolean)
+            }
+            Tokens::BitwiseAnd
+            | Tokens::BitwiseOr
+            | Tokens::BitwiseXor
+            | Tokens::LeftShift
+            | Tokens::RightShift
+            | Tokens::LogicalRightShift => self.integer(&left_type, pos),
+            Tokens::LessThan
+            | Tokens::GreaterThan
+            | Tokens::LessThanEquals
+            | Tokens::GreaterThanEquals => match self.numeric(&left_type, pos) {
+                Some(_) => Ok(Type::Boolean),
+                None => Err(self.error(
+                    pos,
+                    format!("Comparison isn't supported on '{}'", self.prune(&left_type)),
+                )),
+            },
+            _ => {
+                if logical {
+                    self.unify(&Type::Boolean, &left_type, left.pos)?;
+                    Ok(Type::Boolean)
+                } else {
+                    Err(self.error(pos, String::from("Unknown operation")))
+                }
+            }
+        }?;
+
+        Ok(TypedNode::new(
+            TypedKind::BinaryNode {
+                left: Box::new(left),
+                right: Box::new(right),
+                op_token,
+            },
+            typee,
+            pos,
+        ))
+    }
+
+    pub(crate) fn unary_op(
+        &mut self,
+        child: Node,
+        op_token: Token,
+        pos: (Position, Position),
+    ) -> Result<TypedNode, Error> {
+        let child = self.check(&child)?;
+
+        let typee = match op_token.typee {
+            Tokens::Plus | Tokens::Minus => self.numeric(&child.typee, pos).ok_or_else(|| {
+                self.error(
+                    pos,
+                    format!(
+                        "Unary '+' and '-' aren't supported on '{}'",
+                        self.prune(&child.typee)
+                    ),
+                )
+            }),
+            Tokens::BitwiseNot => self.integer(&child.typee, pos),
+            _ => {
+                if op_token.matches(Tokens::Keyword, DynType::String("not".to_string())) {
+                    self.unify(&Type::Boolean, &child.typee, child.pos)?;
+                    Ok(Type::Boolean)
+                } else {
+                    Err(self.error(pos, String::from("Unknown unary operation")))
+                }
+            }
+        }?;
+
+        Ok(TypedNode::new(
+            TypedKind::UnaryNode {
+                node: Box::new(child),
+                op_token,
+            },
+            typee,
+            pos,
+        ))
+    }
+
+    /*
+     * Bitwise operations only work on 'int', defaulting a unresolved type to it
+     */
+    pub(crate) fn integer(
+        &mut self,
+        typee: &Type,
+        pos: (Position, Position),
+    ) -> Result<Type, Error> {
+        match self.numeric(typee, pos) {
+            Some(Type::Int) => Ok(Type::Int),
+            _ => Err(self.error(
+                pos,
+                format!(
+                    "Bitwise operations aren't supported on '{}'",
+                    self.prune(typee)
+                ),
+            )),
+        }
+    }
+}
@@ -0,0 +1,167 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+use bzxc_shared::{Error, Node, Position, Token, Tokens, Type, TypedKind, TypedNode};
+
+use crate::{Symbol, TypeChecker};
+
+impl TypeChecker {
+    pub(crate) fn var_assign(
+        &mut self,
+        name: Token,
+        value: Node,
+        reassignable: bool,
+        pos: (Position, Position),
+    ) -> Result<TypedNode, Error> {
+        if self.scopes.len() == 1 {
+            self.record_global(&name.value.into_string(), &value, reassignable, pos);
+        }
+
+        let value = self.check(&value)?;
+        self.value(&value, String::from("Can't assign a 'void' value"))?;
+
+        let typee = value.typee.clone();
+
+        /* Conflicts with the variable's type later on can point back to its value */
+        let symbol_type = match self.shallow(&typee) {
+            Type::Var(_) => typee.clone(),
+            _ => {
+                let var = Type::fresh();
+                self.unify(&var, &typee, value.pos)?;
+                var
+            }
+        };
+        self.declare(
+            name.value.into_string(),
+            Symbol {
+                typee: symbol_type,
+                reassignable,
+                var_args: false,
+                /* Top level variables are globals, shared by every function */
+                local: self.scopes.len() > 1,
+                scheme: vec![],
+            },
+        );
+
+        /* Like named functions, a function bound to a 'val' can be used at different types */
+        if let (TypedKind::FunDef { captures, .. }, false) = (&value.node, reassignable) {
+            if captures.is_empty() {
+                self.generalize(&name.value.into_string());
+            }
+        }
+
+        Ok(TypedNode::new(
+            TypedKind::VarAssignNode {
+                name,
+                value: Box::new(value),
+                reassignable,
+            },
+            typee,
+            pos,
+        ))
+    }
+
+    pub(crate) fn var_access(
+        &mut self,
+        token: Token,
+        pos: (Position, Position),
+    ) -> Result<TypedNode, Error> {
+        let symbol = self.visible(&token.value.into_string(), pos)?;
+        Ok(TypedNode::new(
+            TypedKind::VarAccessNode { token },
+            self.instantiate(&symbol),
+            pos,
+        ))
+    }
+
+    pub(crate) fn var_reassign(
+        &mut self,
+        name: Token,
+        value: Node,
+        typee: Token,
+        pos: (Position, Position),
+    ) -> Result<TypedNode, Error> {
+        let symbol = self.mutable(&name, pos)?;
+        let value = self.check(&value)?;
+        self.value(&value, String::from("Can't assign a 'void' value"))?;
+        self.unify(&symbol.typee, &value.typee, value.pos)?;
+
+        self.compound(&typee, &symbol.typee, pos)?;
+
+        Ok(TypedNode::new(
+            TypedKind::VarReassignNode {
+                name,
+                typee,
+                value: Box::new(value),
+            },
+            symbol.typee,
+            pos,
+        ))
+    }
+
+    /*
+     * Finds a variable that's about to be modified, in place or by reassignment
+     */
+    pub(crate) fn mutable(
+        &mut self,
+        name: &Token,
+        pos: (Position, Position),
+    ) -> Result<Symbol, Error> {
+        let var_name = name.value.into_string();
+        let symbol = self.visible(&var_name, pos)?;
+
+        if !symbol.reassignable {
+            return Err(self.error(pos, format!("Variable '{}' isn't mutable", var_name)));
+        }
+
+        /* Closures hold a copy of the variables they capture */
+        if self.captured(&var_name) {
+            return Err(self.error(
+                pos,
+                format!("Can't reassign '{}' captured by the closure", var_name),
+            ));
+        }
+
+        Ok(symbol)
+    }
+
+    /*
+     * Checks a compound assignment like '+=' can be applied to the target
+     */
+    pub(crate) fn compound(
+        &mut self,
+        op: &Token,
+        target: &Type,
+        pos: (Position, Position),
+    ) -> Result<(), Error> {
+        if let Tokens::BitwiseAndEquals
+        | Tokens::BitwiseOrEquals
+        | Tokens::BitwiseXorEquals
+        | Tokens::LeftShiftEquals
+        | Tokens::RightShiftEquals
+        | Tokens::LogicalRightShiftEquals = op.typee
+        {
+            self.integer(target, pos)?;
+        } else if op.typee != Tokens::Equals && self.numeric(target, pos).is_none() {
+            return Err(self.error(
+                pos,
+                format!(
+                    "Compound assignment isn't supported on '{}'",
+                    self.prune(target)
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+}
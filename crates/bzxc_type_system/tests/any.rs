@@ -0,0 +1,38 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+mod common;
+
+use common::{accept, reject};
+
+#[test]
+fn rejects_operations_on_two_any_values() {
+    reject(
+        "fun f(a: any, b: any): int { a + b }\n0",
+        "Can't operate on two 'any' values, annotate one of them with a type first",
+    );
+    accept("fun f(a: any, b: int): int { a + b }\n0");
+}
+
+#[test]
+fn rejects_boxing_arrays_and_objects() {
+    reject(
+        "fun wrap(x: any): any { x }\nwrap([1]);\n0",
+        "Can't box a '[int]' into 'any', only 'int', 'float', 'boolean', 'char' and 'string' values can be",
+    );
+    reject(
+        "fun wrap(x: any): any { x }\nwrap({ a: 1 });\n0",
+        "Can't box a '{ a: int }' into 'any', only 'int', 'float', 'boolean', 'char' and 'string' values can be",
+    );
+    accept("fun wrap(x: any): any { x }\nwrap(\"s\");\n0");
+}
@@ -0,0 +1,25 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+mod common;
+
+use common::{accept, reject};
+
+#[test]
+fn checks_array_methods() {
+    accept("var a = [1];\na.push(2);\na.insert(0, 3);\nvar s = a.slice(0, 1);\n0");
+    reject(
+        "var a = [1];\na.push(\"x\");\n0",
+        "Cannot unify 'int' with 'string'",
+    );
+}
@@ -0,0 +1,47 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+mod common;
+
+use common::{accept, reject};
+
+#[test]
+fn rejects_mismatched_arguments() {
+    reject(
+        "fun add(a: int, b: int): int { a + b }\nadd(1, \"two\");\n0",
+        "Cannot unify 'int' with 'string'",
+    );
+    reject(
+        "fun add(a: int, b: int): int { a + b }\nadd(1);\n0",
+        "Expected 2 argument(s) but found 1",
+    );
+}
+
+#[test]
+fn rejects_unknown_variables() {
+    reject("var x = y + 1;\n0", "Variable 'y' not found");
+}
+
+#[test]
+fn rejects_reassigning_a_val() {
+    reject("val x = 1;\nx = 2;\n0", "Variable 'x' isn't mutable");
+    accept("var x = 1;\nx = 2;\n0");
+}
+
+#[test]
+fn rejects_duplicate_methods() {
+    reject(
+        "class Box {\n    fun get(): int { 1 }\n    fun get(): int { 2 }\n}\n0",
+        "Method 'get' already defined on 'Box'",
+    );
+}
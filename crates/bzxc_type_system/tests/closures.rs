@@ -0,0 +1,41 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+mod common;
+
+use common::{accept, reject};
+
+#[test]
+fn closures_are_typed_as_functions() {
+    accept("fun f(n: int): fun(int): int {\n    fun(x: int): int { x + n }\n}\nf(1)(2);\n0");
+    reject(
+        "fun f(n: int): fun(int): int {\n    fun(x: int): int { x + n }\n}\nf(1)(\"a\");\n0",
+        "Cannot unify 'int' with 'string'",
+    );
+}
+
+#[test]
+fn rejects_reassigning_captured_variables() {
+    reject(
+        "fun f(): fun(): int {\n    var n = 1;\n    fun(): int { n = 2; n }\n}\n0",
+        "Can't reassign 'n' captured by the closure",
+    );
+}
+
+#[test]
+fn rejects_methods_reading_enclosing_locals() {
+    reject(
+        "fun f(n: int): int {\n    class A {\n        fun get(): int { n }\n    }\n    n\n}\n0",
+        "Can't access 'n' from an enclosing function",
+    );
+}
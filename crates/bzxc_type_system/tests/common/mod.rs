@@ -0,0 +1,45 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+/*
+ * Helpers shared by the test files, each file only uses some of them
+ */
+#![allow(dead_code)]
+
+use bzxc_lexer::Lexer;
+use bzxc_parser::parser::Parser;
+use bzxc_shared::{Error, TypedNode};
+use bzxc_type_system::TypeChecker;
+
+pub fn check(src: &'static str) -> Result<TypedNode, Error> {
+    let tokens = Lexer::new("test.bzx", src).lex().unwrap();
+    let parsed = Parser::new(tokens).parse();
+    assert!(parsed.error.is_none(), "{:?}", parsed.error);
+    TypeChecker::new(parsed.node.unwrap()).typed_node()
+}
+
+pub fn accept(src: &'static str) {
+    if let Err(error) = check(src) {
+        panic!("Rejected with '{}'", error.description);
+    }
+}
+
+pub fn reject(src: &'static str, description: &str) -> Error {
+    match check(src) {
+        Ok(_) => panic!("Accepted, expected '{}'", description),
+        Err(error) => {
+            assert_eq!(error.description, description);
+            error
+        }
+    }
+}
@@ -0,0 +1,34 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+mod common;
+
+use common::{accept, reject};
+
+#[test]
+fn rejects_if_without_else_used_as_a_value() {
+    reject(
+        "var x = if true { 1 };\n0",
+        "'if' without 'else' used as a value",
+    );
+    accept("if true { 1 };\n0");
+}
+
+#[test]
+fn rejects_if_branches_of_different_types() {
+    reject(
+        "var x = if true { 1 } else { \"a\" };\n0",
+        "The branches of the 'if' have different types, 'int' and 'string'",
+    );
+    accept("var x = if true { 1 } else if false { 2 } else { 3 };\n0");
+}
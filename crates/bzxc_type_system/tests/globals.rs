@@ -0,0 +1,38 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+mod common;
+
+use common::{accept, reject};
+
+#[test]
+fn functions_share_globals() {
+    accept("var count = 0;\nfun bump() { count = count + 1; }\nbump();\n0");
+    reject(
+        "val limit = 3;\nfun raise() { limit = 4; }\n0",
+        "Variable 'limit' isn't mutable",
+    );
+}
+
+#[test]
+fn rejects_reading_a_global_before_it_is_initialized() {
+    reject(
+        "printf(\"%d\", f());\nvar g = 5;\nfun f(): int { g }\n0",
+        "'f' reads 'g' before it's initialized",
+    );
+    reject(
+        "printf(\"%d\", f());\nvar g = 5;\nfun f(): int { h() }\nfun h(): int { g }\n0",
+        "'f' reads 'g' before it's initialized",
+    );
+    accept("var g = 5;\nprintf(\"%d\", f());\nfun f(): int { g }\n0");
+}
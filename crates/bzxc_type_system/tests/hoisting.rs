@@ -0,0 +1,36 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+mod common;
+
+use common::{accept, reject};
+
+#[test]
+fn top_level_functions_are_hoisted() {
+    accept("printf(\"%d\", later(1));\nfun later(n: int): int { n }\n0");
+    accept(
+        "fun a(n: int): int { if n == 0 { 0 } else { b(n - 1) } }\nfun b(n: int): int { a(n) }\n0",
+    );
+    reject(
+        "printf(\"%d\", later(\"a\"));\nfun later(n: int): int { n }\n0",
+        "Cannot unify 'int' with 'string'",
+    );
+}
+
+#[test]
+fn nested_functions_are_not_hoisted() {
+    reject(
+        "fun outer(): int {\n    var x = inner();\n    fun inner(): int { 1 }\n    x\n}\n0",
+        "Variable 'inner' not found",
+    );
+}
@@ -0,0 +1,49 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+mod common;
+
+use common::{accept, reject};
+
+#[test]
+fn infers_unannotated_arguments() {
+    accept("fun add(a, b) { a + b }\nadd(1, 2);\n0");
+}
+
+#[test]
+fn uses_an_unannotated_function_at_different_types() {
+    accept("fun id(x) { x }\nvar n = id(1) + 1;\nvar s = id(\"one\");\n0");
+    accept("val wrap = fun(x) { [x] };\nwrap(1);\nwrap(\"one\");\n0");
+}
+
+#[test]
+fn accepts_a_function_that_is_never_called() {
+    accept("fun second(x, y) { y }\n0");
+}
+
+#[test]
+fn rejects_conflicting_calls_of_an_inferred_function() {
+    reject(
+        "fun add(a, b) { a + b }\nadd(1, 2);\nadd(1.5, 2.5);\n0",
+        "Cannot unify 'int' with 'float'",
+    );
+}
+
+#[test]
+fn notes_both_sides_of_a_conflict() {
+    let error = reject(
+        "fun id(x) { x }\nvar n = id(1);\nn = id(\"one\");\n0",
+        "Cannot unify 'int' with 'string'",
+    );
+    assert_eq!(error.notes.map_or(0, |x| x.len()), 2);
+}
@@ -0,0 +1,70 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+mod common;
+
+use common::{accept, reject};
+
+#[test]
+fn default_step_matches_the_variable() {
+    accept("for i = 0.0 to 1.0 { var x = i; }\n0");
+    accept("for i = 0 to 10 { var x = i; }\n0");
+    reject(
+        "for i = 0.0 to 1.0 step 1 { var x = i; }\n0",
+        "Cannot unify 'float' with 'int'",
+    );
+}
+
+#[test]
+fn break_and_continue_need_a_loop() {
+    accept(
+        "outer: while true {\n    for i = 0 to 3 { if i == 1 { continue outer; }; break; };\n};\n0",
+    );
+    reject("break;\n0", "'break' outside of a loop");
+    reject("if true { continue; };\n0", "'continue' outside of a loop");
+    reject(
+        "while true {\n    fun f() { break; }\n    break;\n};\n0",
+        "'break' outside of a loop",
+    );
+    reject(
+        "inner: while true { break outer; };\n0",
+        "Loop 'outer' not found",
+    );
+}
+
+#[test]
+fn ranges_count_numbers() {
+    accept("for i = 10 through 0 step -5 { var x = i; }\n0");
+    reject(
+        "for i = \"a\" to \"b\" { var x = i; }\n0",
+        "Expected 'int' or 'float' but found 'string'",
+    );
+    reject(
+        "for i = 0 to 1.5 { var x = i; }\n0",
+        "Cannot unify 'int' with 'float'",
+    );
+}
+
+#[test]
+fn for_in_types_the_element() {
+    accept("for x in [1, 2] { var y = x * 2; }\n0");
+    reject(
+        "for x in [\"a\"] { var y = x * 2; }\n0",
+        "Cannot unify 'string' with 'int'",
+    );
+    reject("for x in 5 { var y = x; }\n0", "Can't iterate over 'int'");
+    reject(
+        "class A {\n    fun next(): int { 1 }\n}\nfor x in new A() { var y = x; }\n0",
+        "Class 'A' needs 'has_next()' and 'next()' methods to be iterated",
+    );
+}
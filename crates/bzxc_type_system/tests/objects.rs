@@ -0,0 +1,37 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+use bzxc_lexer::Lexer;
+use bzxc_parser::parser::Parser;
+use bzxc_type_system::TypeChecker;
+
+#[test]
+fn registers_each_object_layout_once() {
+    let tokens = Lexer::new(
+        "test.bzx",
+        "class Point {\n    var x = 0;\n}\nval a = { x: 1, y: [{ z: 2.0 }] };\nval b = { x: 3, y: [{ z: 4.0 }] };\nb.y[0].z;\n0",
+    )
+    .lex()
+    .unwrap();
+    let mut checker = TypeChecker::new(Parser::new(tokens).parse().node.unwrap());
+    checker.typed_node().unwrap();
+
+    let mut names = checker.records.keys().cloned().collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(
+        names,
+        vec!["Point", "{ x: int, y: [{ z: float }] }", "{ z: float }"]
+    );
+    let record = &checker.records["{ x: int, y: [{ z: float }] }"];
+    assert_eq!(record.field("y").map(|x| x.0), Some(1));
+}
@@ -0,0 +1,65 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+mod common;
+
+use common::{accept, reject};
+
+#[test]
+fn and_or_take_booleans() {
+    reject("1 and true;\n0", "Cannot unify 'int' with 'boolean'");
+    reject(
+        "true or \"yes\";\n0",
+        "Cannot unify 'boolean' with 'string'",
+    );
+    accept("var x = 0;\nx != 0 and 10 / x > 1;\n0");
+}
+
+#[test]
+fn bitwise_operators_take_ints() {
+    reject(
+        "1.5 & 2.5;\n0",
+        "Bitwise operations aren't supported on 'float'",
+    );
+    reject(
+        "\"a\" << \"b\";\n0",
+        "Bitwise operations aren't supported on 'string'",
+    );
+    reject(
+        "var f = 1.5;\nf |= 2.0;\n0",
+        "Bitwise operations aren't supported on 'float'",
+    );
+    reject("~1.5;\n0", "Bitwise operations aren't supported on 'float'");
+    accept("var x = 1;\nx <<= 2;\nx xor= ~x & 3;\n0");
+}
+
+#[test]
+fn unary_operators_check_their_operand() {
+    reject(
+        "-\"a\";\n0",
+        "Unary '+' and '-' aren't supported on 'string'",
+    );
+    reject(
+        "var b = true;\n-b;\n0",
+        "Unary '+' and '-' aren't supported on 'boolean'",
+    );
+    reject("not 1;\n0", "Cannot unify 'boolean' with 'int'");
+    accept("var x = 1.5;\n-x;\nvar b = true;\n!b;\n0");
+}
+
+#[test]
+fn equality_needs_the_same_types() {
+    reject("1 == \"a\";\n0", "Cannot unify 'int' with 'string'");
+    reject("[1] == [1.0];\n0", "Cannot unify '[int]' with '[float]'");
+    accept("{ x: 1 } != { x: 2 };\n0");
+}